@@ -0,0 +1,334 @@
+use crate::config::{ArrowMode, ArrowNumber, ExportConfig};
+use crate::db::{ColumnInfo, ColumnKind, Value};
+use crate::writer::RowWriter;
+use anyhow::{anyhow, bail, Context, Result};
+use arrow_array::builder::{
+    BinaryBuilder, BooleanBuilder, Decimal128Builder, Float64Builder, Int64Builder,
+    LargeBinaryBuilder, LargeStringBuilder, StringBuilder, TimestampMicrosecondBuilder,
+    TimestampNanosecondBuilder, TimestampSecondBuilder,
+};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_ipc::writer::{FileWriter, StreamWriter};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use std::io::Write;
+use std::sync::Arc;
+
+/// Decimal128的最大精度
+const MAX_DECIMAL_PRECISION: u32 = 38;
+/// 不超过此精度的整数列使用Int64
+const MAX_INT64_PRECISION: u32 = 18;
+
+/// 根据列信息确定Arrow类型
+///
+/// 带精度的NUMBER为Decimal128，NUMBER(p≤18,0)为Int64，未指定精度的整数为Decimal128(38,0)，
+/// 其余未指定精度的NUMBER按 `number` 配置，BINARY_FLOAT/DOUBLE为Float64；
+/// DATE为秒级时间戳，TIMESTAMP按小数位数为微秒或纳秒级时间戳，带时区的时间戳转换为UTC
+pub fn data_type(column: &ColumnInfo, number: ArrowNumber, number_scale: u8) -> DataType {
+    match column.kind {
+        ColumnKind::Integer => match column.precision {
+            Some(p) if p <= MAX_INT64_PRECISION => DataType::Int64,
+            Some(p) if p <= MAX_DECIMAL_PRECISION => DataType::Decimal128(p as u8, 0),
+            _ => DataType::Decimal128(MAX_DECIMAL_PRECISION as u8, 0),
+        },
+        ColumnKind::Number => match (column.precision, column.scale) {
+            (Some(p), Some(s)) if p <= MAX_DECIMAL_PRECISION && (0..=p as i32).contains(&s) => {
+                DataType::Decimal128(p as u8, s as i8)
+            }
+            // 聚合、计算列及FLOAT(p)等未指定精度的NUMBER
+            _ => match number {
+                ArrowNumber::Decimal => DataType::Decimal128(MAX_DECIMAL_PRECISION as u8, number_scale as i8),
+                ArrowNumber::String => DataType::Utf8,
+                ArrowNumber::Float64 => DataType::Float64,
+            },
+        },
+        ColumnKind::Float => DataType::Float64,
+        ColumnKind::Date => DataType::Timestamp(TimeUnit::Second, None),
+        ColumnKind::Timestamp => DataType::Timestamp(time_unit(column), None),
+        ColumnKind::TimestampTz => DataType::Timestamp(time_unit(column), Some("UTC".into())),
+        ColumnKind::Boolean => DataType::Boolean,
+        ColumnKind::Binary => DataType::Binary,
+        ColumnKind::Blob => DataType::LargeBinary,
+        ColumnKind::Clob => DataType::LargeUtf8,
+        ColumnKind::Text | ColumnKind::Interval | ColumnKind::Other => DataType::Utf8,
+    }
+}
+
+fn time_unit(column: &ColumnInfo) -> TimeUnit {
+    match column.scale {
+        Some(scale) if scale > 6 => TimeUnit::Nanosecond,
+        _ => TimeUnit::Microsecond,
+    }
+}
+
+/// Arrow IPC写入器，每 `batch_size` 行组成一个记录批次
+pub struct ArrowWriter {
+    schema: SchemaRef,
+    builders: Vec<ColumnBuilder>,
+    batch_size: usize,
+    rows: usize,
+    ipc: Option<IpcWriter>,
+}
+
+/// IPC写入器写入内存缓冲区，每个批次写完后转移到输出
+enum IpcWriter {
+    File(FileWriter<Vec<u8>>),
+    Stream(StreamWriter<Vec<u8>>),
+}
+
+impl ArrowWriter {
+    /// `columns` 为写入器收到的值对应的列，`fetch_size` 为未配置批次大小时的默认值
+    pub fn new(config: &ExportConfig, columns: &[ColumnInfo], fetch_size: usize) -> Result<Self> {
+        let batch_size = config.arrow.batch_size.unwrap_or(fetch_size).max(1);
+        let (number, number_scale) = (config.arrow.number, config.arrow.number_scale);
+        let fields: Vec<Field> = columns
+            .iter()
+            .map(|c| Field::new(c.name.clone(), data_type(c, number, number_scale), c.nullable))
+            .collect();
+        let schema = Arc::new(Schema::new(fields));
+        let builders = schema
+            .fields()
+            .iter()
+            .map(|field| ColumnBuilder::new(field.data_type(), batch_size))
+            .collect::<Result<Vec<_>>>()?;
+        let ipc = match config.arrow.mode {
+            ArrowMode::File => IpcWriter::File(FileWriter::try_new(Vec::new(), &schema)?),
+            ArrowMode::Stream => IpcWriter::Stream(StreamWriter::try_new(Vec::new(), &schema)?),
+        };
+        Ok(Self {
+            schema,
+            builders,
+            batch_size,
+            rows: 0,
+            ipc: Some(ipc),
+        })
+    }
+
+    /// 将缓冲的行写为一个记录批次
+    fn flush(&mut self, out: &mut dyn Write) -> Result<()> {
+        if self.rows > 0 {
+            let arrays: Vec<ArrayRef> = self.builders.iter_mut().map(ColumnBuilder::finish).collect();
+            let batch = RecordBatch::try_new(Arc::clone(&self.schema), arrays)?;
+            match self.ipc {
+                Some(IpcWriter::File(ref mut writer)) => writer.write(&batch)?,
+                Some(IpcWriter::Stream(ref mut writer)) => writer.write(&batch)?,
+                None => bail!("Arrow writer is already finished"),
+            }
+            self.rows = 0;
+        }
+        self.drain(out)
+    }
+
+    fn drain(&mut self, out: &mut dyn Write) -> Result<()> {
+        let buffer = match self.ipc {
+            Some(IpcWriter::File(ref mut writer)) => writer.get_mut(),
+            Some(IpcWriter::Stream(ref mut writer)) => writer.get_mut(),
+            None => return Ok(()),
+        };
+        out.write_all(buffer)?;
+        buffer.clear();
+        Ok(())
+    }
+}
+
+impl RowWriter for ArrowWriter {
+    /// 列名已包含在schema中，开头只写入IPC头部
+    fn begin(&mut self, out: &mut dyn Write, _headers: &[String]) -> Result<()> {
+        self.drain(out)
+    }
+
+    fn write_row(&mut self, out: &mut dyn Write, values: Vec<Value>) -> Result<()> {
+        for ((builder, value), field) in self.builders.iter_mut().zip(values).zip(self.schema.fields()) {
+            builder
+                .append(value)
+                .with_context(|| format!("Invalid value for column {} ({})", field.name(), field.data_type()))?;
+        }
+        self.rows += 1;
+        if self.rows >= self.batch_size {
+            self.flush(out)?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, out: &mut dyn Write) -> Result<()> {
+        self.flush(out)?;
+        match self.ipc {
+            Some(IpcWriter::File(ref mut writer)) => writer.finish()?,
+            Some(IpcWriter::Stream(ref mut writer)) => writer.finish()?,
+            None => {}
+        }
+        self.drain(out)?;
+        self.ipc = None;
+        Ok(())
+    }
+}
+
+/// 按列类型追加值的构建器
+enum ColumnBuilder {
+    Utf8(StringBuilder),
+    LargeUtf8(LargeStringBuilder),
+    Int64(Int64Builder),
+    Float64(Float64Builder),
+    Decimal(Decimal128Builder, i8),
+    Boolean(BooleanBuilder),
+    Second(TimestampSecondBuilder),
+    Microsecond(TimestampMicrosecondBuilder),
+    Nanosecond(TimestampNanosecondBuilder),
+    Binary(BinaryBuilder),
+    LargeBinary(LargeBinaryBuilder),
+}
+
+impl ColumnBuilder {
+    fn new(data_type: &DataType, capacity: usize) -> Result<Self> {
+        Ok(match data_type {
+            DataType::Utf8 => ColumnBuilder::Utf8(StringBuilder::with_capacity(capacity, capacity * 16)),
+            DataType::LargeUtf8 => ColumnBuilder::LargeUtf8(LargeStringBuilder::new()),
+            DataType::Int64 => ColumnBuilder::Int64(Int64Builder::with_capacity(capacity)),
+            DataType::Float64 => ColumnBuilder::Float64(Float64Builder::with_capacity(capacity)),
+            DataType::Decimal128(p, s) => ColumnBuilder::Decimal(
+                Decimal128Builder::with_capacity(capacity).with_precision_and_scale(*p, *s)?,
+                *s,
+            ),
+            DataType::Boolean => ColumnBuilder::Boolean(BooleanBuilder::with_capacity(capacity)),
+            DataType::Timestamp(TimeUnit::Second, _) => {
+                ColumnBuilder::Second(TimestampSecondBuilder::with_capacity(capacity))
+            }
+            DataType::Timestamp(TimeUnit::Microsecond, tz) => ColumnBuilder::Microsecond(
+                TimestampMicrosecondBuilder::with_capacity(capacity).with_timezone_opt(tz.clone()),
+            ),
+            DataType::Timestamp(TimeUnit::Nanosecond, tz) => ColumnBuilder::Nanosecond(
+                TimestampNanosecondBuilder::with_capacity(capacity).with_timezone_opt(tz.clone()),
+            ),
+            DataType::Binary => ColumnBuilder::Binary(BinaryBuilder::new()),
+            DataType::LargeBinary => ColumnBuilder::LargeBinary(LargeBinaryBuilder::new()),
+            other => bail!("Unsupported Arrow type {}", other),
+        })
+    }
+
+    fn append(&mut self, value: Value) -> Result<()> {
+        match (self, value) {
+            (ColumnBuilder::Utf8(b), Value::Null) => b.append_null(),
+            (ColumnBuilder::Utf8(b), value) => b.append_value(value.into_string()),
+            (ColumnBuilder::LargeUtf8(b), Value::Null) => b.append_null(),
+            (ColumnBuilder::LargeUtf8(b), value) => b.append_value(value.into_string()),
+            (ColumnBuilder::Int64(b), Value::Null) => b.append_null(),
+            (ColumnBuilder::Int64(b), Value::Number(text)) => {
+                b.append_value(text.parse().map_err(|_| anyhow!("{} is not an integer", text))?)
+            }
+            (ColumnBuilder::Float64(b), Value::Null) => b.append_null(),
+            (ColumnBuilder::Float64(b), Value::Number(text)) => {
+                b.append_value(text.parse().map_err(|_| anyhow!("{} is not a number", text))?)
+            }
+            (ColumnBuilder::Decimal(b, _), Value::Null) => b.append_null(),
+            (ColumnBuilder::Decimal(b, scale), Value::Number(text)) => b.append_value(parse_decimal(&text, *scale)?),
+            (ColumnBuilder::Boolean(b), Value::Null) => b.append_null(),
+            (ColumnBuilder::Boolean(b), Value::Boolean(v)) => b.append_value(v),
+            (ColumnBuilder::Second(b), Value::Null) => b.append_null(),
+            (ColumnBuilder::Second(b), Value::DateTime { value, .. }) => b.append_value(value.and_utc().timestamp()),
+            (ColumnBuilder::Microsecond(b), Value::Null) => b.append_null(),
+            (ColumnBuilder::Microsecond(b), Value::DateTime { value, .. }) => {
+                b.append_value(value.and_utc().timestamp_micros())
+            }
+            (ColumnBuilder::Microsecond(b), Value::DateTimeTz { value, .. }) => b.append_value(value.timestamp_micros()),
+            (ColumnBuilder::Nanosecond(b), Value::Null) => b.append_null(),
+            (ColumnBuilder::Nanosecond(b), Value::DateTime { value, .. }) => b.append_value(
+                value
+                    .and_utc()
+                    .timestamp_nanos_opt()
+                    .ok_or_else(|| anyhow!("{} is out of range for nanosecond timestamps", value))?,
+            ),
+            (ColumnBuilder::Nanosecond(b), Value::DateTimeTz { value, .. }) => b.append_value(
+                value
+                    .timestamp_nanos_opt()
+                    .ok_or_else(|| anyhow!("{} is out of range for nanosecond timestamps", value))?,
+            ),
+            (ColumnBuilder::Binary(b), Value::Null) => b.append_null(),
+            (ColumnBuilder::Binary(b), Value::Binary(bytes)) => b.append_value(bytes),
+            (ColumnBuilder::LargeBinary(b), Value::Null) => b.append_null(),
+            (ColumnBuilder::LargeBinary(b), Value::Binary(bytes)) => b.append_value(bytes),
+            (_, value) => bail!("unexpected value {:?}", value),
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        match self {
+            ColumnBuilder::Utf8(b) => Arc::new(b.finish()),
+            ColumnBuilder::LargeUtf8(b) => Arc::new(b.finish()),
+            ColumnBuilder::Int64(b) => Arc::new(b.finish()),
+            ColumnBuilder::Float64(b) => Arc::new(b.finish()),
+            ColumnBuilder::Decimal(b, _) => Arc::new(b.finish()),
+            ColumnBuilder::Boolean(b) => Arc::new(b.finish()),
+            ColumnBuilder::Second(b) => Arc::new(b.finish()),
+            ColumnBuilder::Microsecond(b) => Arc::new(b.finish()),
+            ColumnBuilder::Nanosecond(b) => Arc::new(b.finish()),
+            ColumnBuilder::Binary(b) => Arc::new(b.finish()),
+            ColumnBuilder::LargeBinary(b) => Arc::new(b.finish()),
+        }
+    }
+}
+
+/// 将十进制文本转换为按 `scale` 缩放的整数，多余的小数位四舍五入
+pub fn parse_decimal(text: &str, scale: i8) -> Result<i128> {
+    let invalid = || anyhow!("{} is not a decimal number", text);
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let (int_part, frac_part) = digits.split_once('.').unwrap_or((digits, ""));
+    if int_part.is_empty() && frac_part.is_empty()
+        || !int_part.chars().chain(frac_part.chars()).all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    let scale = scale as usize;
+    let mut value: i128 = 0;
+    for c in int_part.chars().chain(frac_part.chars().chain(std::iter::repeat('0')).take(scale)) {
+        value = value
+            .checked_mul(10)
+            .and_then(|v| v.checked_add(c.to_digit(10).unwrap_or(0) as i128))
+            .ok_or_else(invalid)?;
+    }
+    if frac_part.chars().nth(scale).is_some_and(|c| c >= '5') {
+        value = value.checked_add(1).ok_or_else(invalid)?;
+    }
+    Ok(if negative { -value } else { value })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number_column(precision: Option<u32>, scale: Option<i32>) -> ColumnInfo {
+        ColumnInfo {
+            name: "N".to_string(),
+            type_name: "NUMBER".to_string(),
+            kind: if scale == Some(0) { ColumnKind::Integer } else { ColumnKind::Number },
+            precision,
+            scale,
+            size: None,
+            nullable: true,
+        }
+    }
+
+    #[test]
+    fn unconstrained_numbers_keep_precision() {
+        let column = number_column(None, None);
+        assert_eq!(data_type(&column, ArrowNumber::Decimal, 10), DataType::Decimal128(38, 10));
+        assert_eq!(data_type(&column, ArrowNumber::String, 10), DataType::Utf8);
+        assert_eq!(data_type(&column, ArrowNumber::Float64, 10), DataType::Float64);
+        assert_eq!(data_type(&number_column(None, Some(0)), ArrowNumber::Float64, 10), DataType::Decimal128(38, 0));
+        assert_eq!(data_type(&number_column(Some(10), Some(0)), ArrowNumber::Decimal, 10), DataType::Int64);
+        assert_eq!(data_type(&number_column(Some(12), Some(2)), ArrowNumber::Decimal, 10), DataType::Decimal128(12, 2));
+    }
+
+    #[test]
+    fn parse_decimal_rounds_and_checks_range() {
+        assert_eq!(parse_decimal("12.345", 2).unwrap(), 1235);
+        assert_eq!(parse_decimal("-0.5", 0).unwrap(), -1);
+        assert_eq!(parse_decimal("123", 3).unwrap(), 123000);
+        assert!(parse_decimal("abc", 0).is_err());
+        let max = i128::MAX.to_string();
+        assert!(parse_decimal(&format!("{}.9", max), 0).is_err());
+    }
+}
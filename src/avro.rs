@@ -0,0 +1,335 @@
+use crate::arrow::parse_decimal;
+use crate::config::{AvroCodec, ExportConfig};
+use crate::db::{ColumnInfo, ColumnKind, Value};
+use crate::writer::RowWriter;
+use anyhow::{anyhow, bail, Context, Result};
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde_json::{json, Value as Json};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+/// 对象容器文件的文件头标识
+const MAGIC: &[u8; 4] = b"Obj\x01";
+/// decimal的最大精度（按i128计算未缩放值）
+const MAX_DECIMAL_PRECISION: u32 = 38;
+/// 不超过此精度的整数列使用long
+const MAX_LONG_PRECISION: u32 = 18;
+
+/// Avro字段类型（不含可空的union）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AvroType {
+    Long,
+    Double,
+    Decimal { precision: u32, scale: i8 },
+    /// DATE，无时区的毫秒级时间戳
+    LocalTimestampMillis,
+    /// TIMESTAMP，无时区的微秒级时间戳
+    LocalTimestampMicros,
+    /// 带时区的时间戳，转换为UTC的微秒级时间戳
+    TimestampMicros,
+    Boolean,
+    Bytes,
+    String,
+}
+
+impl AvroType {
+    /// 根据列信息确定Avro类型，与Arrow格式的映射规则一致
+    fn from_column(column: &ColumnInfo) -> Self {
+        match column.kind {
+            ColumnKind::Integer => match column.precision {
+                Some(p) if p <= MAX_LONG_PRECISION => AvroType::Long,
+                Some(p) if p <= MAX_DECIMAL_PRECISION => AvroType::Decimal { precision: p, scale: 0 },
+                _ => AvroType::Double,
+            },
+            ColumnKind::Number => match (column.precision, column.scale) {
+                (Some(p), Some(s)) if p <= MAX_DECIMAL_PRECISION && (0..=p as i32).contains(&s) => {
+                    AvroType::Decimal { precision: p, scale: s as i8 }
+                }
+                _ => AvroType::Double,
+            },
+            ColumnKind::Float => AvroType::Double,
+            ColumnKind::Date => AvroType::LocalTimestampMillis,
+            ColumnKind::Timestamp => AvroType::LocalTimestampMicros,
+            ColumnKind::TimestampTz => AvroType::TimestampMicros,
+            ColumnKind::Boolean => AvroType::Boolean,
+            ColumnKind::Binary | ColumnKind::Blob => AvroType::Bytes,
+            ColumnKind::Text | ColumnKind::Clob | ColumnKind::Interval | ColumnKind::Other => AvroType::String,
+        }
+    }
+
+    fn schema(self) -> Json {
+        match self {
+            AvroType::Long => json!("long"),
+            AvroType::Double => json!("double"),
+            AvroType::Decimal { precision, scale } => {
+                json!({"type": "bytes", "logicalType": "decimal", "precision": precision, "scale": scale})
+            }
+            AvroType::LocalTimestampMillis => json!({"type": "long", "logicalType": "local-timestamp-millis"}),
+            AvroType::LocalTimestampMicros => json!({"type": "long", "logicalType": "local-timestamp-micros"}),
+            AvroType::TimestampMicros => json!({"type": "long", "logicalType": "timestamp-micros"}),
+            AvroType::Boolean => json!("boolean"),
+            AvroType::Bytes => json!("bytes"),
+            AvroType::String => json!("string"),
+        }
+    }
+}
+
+/// 生成记录的schema，可空列为 `["null", 类型]` 的union；列名转换为合法的Avro名称
+fn schema(config: &ExportConfig, columns: &[ColumnInfo]) -> Result<Json> {
+    let mut names = HashSet::new();
+    let mut fields = Vec::with_capacity(columns.len());
+    for column in columns {
+        let name = avro_name(&column.name);
+        if name != column.name {
+            warn!("Column {} is written as Avro field {}", column.name, name);
+        }
+        if !names.insert(name.clone()) {
+            bail!(
+                "Column {} maps to duplicate Avro field name {} (rename it with [export.mapping])",
+                column.name,
+                name
+            );
+        }
+        let field_type = AvroType::from_column(column).schema();
+        fields.push(if column.nullable {
+            json!({"name": name, "type": ["null", field_type], "default": null, "doc": column.type_name})
+        } else {
+            json!({"name": name, "type": field_type, "doc": column.type_name})
+        });
+    }
+
+    let mut record = json!({"type": "record", "name": record_name(config), "fields": fields});
+    let namespace = match (&config.avro.namespace, &config.source_table) {
+        (Some(namespace), _) => Some(namespace.clone()),
+        (None, Some(table)) => Some(avro_name(&table.owner)),
+        (None, None) => None,
+    };
+    if let Some(namespace) = namespace {
+        record["namespace"] = json!(namespace);
+    }
+    Ok(record)
+}
+
+/// 记录名：配置的名称，表模式下为源表名，查询模式下为输出文件名（不含扩展名，写入标准输出时为 `stdout`）
+fn record_name(config: &ExportConfig) -> String {
+    match (&config.avro.name, &config.source_table) {
+        (Some(name), _) => name.clone(),
+        (None, Some(table)) => avro_name(&table.name),
+        (None, None) if config.writes_to_stdout() => "stdout".to_string(),
+        (None, None) => avro_name(
+            &Path::new(&config.output_file)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        ),
+    }
+}
+
+/// Avro名称只能包含字母、数字和 `_`，且不以数字开头，其他字符替换为 `_`
+fn avro_name(name: &str) -> String {
+    let mut result: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+    if !result.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        result.insert(0, '_');
+    }
+    result
+}
+
+/// 是否为合法的Avro全名（以 `.` 分隔的名称）
+pub fn is_valid_name(name: &str) -> bool {
+    name.split('.').all(|part| {
+        part.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+/// Avro对象容器文件写入器，行数据达到 `block_size` 字节后压缩写出一个数据块
+pub struct AvroWriter {
+    schema: Json,
+    types: Vec<AvroType>,
+    nullable: Vec<bool>,
+    names: Vec<String>,
+    codec: AvroCodec,
+    block_size: usize,
+    sync: [u8; 16],
+    /// 当前数据块中已编码的行
+    block: Vec<u8>,
+    rows: u64,
+    buffer: Vec<u8>,
+}
+
+impl AvroWriter {
+    /// `columns` 为写入器收到的值对应的列
+    pub fn new(config: &ExportConfig, columns: &[ColumnInfo]) -> Result<Self> {
+        let block_size = config.avro.block_size.max(1);
+        Ok(Self {
+            schema: schema(config, columns)?,
+            types: columns.iter().map(AvroType::from_column).collect(),
+            nullable: columns.iter().map(|c| c.nullable).collect(),
+            names: columns.iter().map(|c| c.name.clone()).collect(),
+            codec: config.avro.codec,
+            block_size,
+            sync: sync_marker(&config.output_file),
+            block: Vec::with_capacity(block_size + 4096),
+            rows: 0,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// 写出当前数据块：行数、压缩后的字节数、数据及同步标记
+    fn flush(&mut self, out: &mut dyn Write) -> Result<()> {
+        if self.rows == 0 {
+            return Ok(());
+        }
+        let compressed = match self.codec {
+            AvroCodec::Null => None,
+            AvroCodec::Deflate => {
+                let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(&self.block)?;
+                Some(encoder.finish()?)
+            }
+            // snappy压缩的数据后附加未压缩数据的CRC32（大端序）
+            AvroCodec::Snappy => {
+                let mut data = snap::raw::Encoder::new()
+                    .compress_vec(&self.block)
+                    .context("Failed to compress Avro block")?;
+                data.extend_from_slice(&crc32fast::hash(&self.block).to_be_bytes());
+                Some(data)
+            }
+        };
+        let data = compressed.as_deref().unwrap_or(&self.block);
+
+        self.buffer.clear();
+        write_long(&mut self.buffer, self.rows as i64);
+        write_long(&mut self.buffer, data.len() as i64);
+        out.write_all(&self.buffer)?;
+        out.write_all(data)?;
+        out.write_all(&self.sync)?;
+        self.block.clear();
+        self.rows = 0;
+        Ok(())
+    }
+}
+
+impl RowWriter for AvroWriter {
+    /// 文件头：标识、元数据（schema及压缩方式）和同步标记
+    fn begin(&mut self, out: &mut dyn Write, _headers: &[String]) -> Result<()> {
+        let codec = match self.codec {
+            AvroCodec::Null => "null",
+            AvroCodec::Deflate => "deflate",
+            AvroCodec::Snappy => "snappy",
+        };
+        let mut header = MAGIC.to_vec();
+        write_long(&mut header, 2);
+        write_bytes(&mut header, b"avro.schema");
+        write_bytes(&mut header, serde_json::to_string(&self.schema)?.as_bytes());
+        write_bytes(&mut header, b"avro.codec");
+        write_bytes(&mut header, codec.as_bytes());
+        write_long(&mut header, 0);
+        header.extend_from_slice(&self.sync);
+        out.write_all(&header)?;
+        Ok(())
+    }
+
+    fn write_row(&mut self, out: &mut dyn Write, values: Vec<Value>) -> Result<()> {
+        for (i, value) in values.into_iter().enumerate() {
+            if self.nullable[i] {
+                write_long(&mut self.block, if matches!(value, Value::Null) { 0 } else { 1 });
+            } else if matches!(value, Value::Null) {
+                // 非空字段没有null分支，跳过会使数据块无法解析
+                bail!("NULL value in non-nullable column {}", self.names[i]);
+            }
+            write_value(&mut self.block, self.types[i], value)
+                .with_context(|| format!("Invalid value for column {}", self.names[i]))?;
+        }
+        self.rows += 1;
+        if self.block.len() >= self.block_size {
+            self.flush(out)?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, out: &mut dyn Write) -> Result<()> {
+        self.flush(out)
+    }
+}
+
+/// 按字段类型编码值，可空列的NULL只写入union分支
+fn write_value(out: &mut Vec<u8>, avro_type: AvroType, value: Value) -> Result<()> {
+    match (avro_type, value) {
+        (_, Value::Null) => {}
+        (AvroType::Long, Value::Number(text)) => {
+            write_long(out, text.parse().map_err(|_| anyhow!("{} is not an integer", text))?)
+        }
+        (AvroType::Double, Value::Number(text)) => {
+            let number: f64 = text.parse().map_err(|_| anyhow!("{} is not a number", text))?;
+            out.extend_from_slice(&number.to_le_bytes());
+        }
+        (AvroType::Decimal { scale, .. }, Value::Number(text)) => {
+            write_bytes(out, &decimal_bytes(parse_decimal(&text, scale)?))
+        }
+        (AvroType::LocalTimestampMillis, Value::DateTime { value, .. }) => {
+            write_long(out, value.and_utc().timestamp_millis())
+        }
+        (AvroType::LocalTimestampMicros, Value::DateTime { value, .. }) => {
+            write_long(out, value.and_utc().timestamp_micros())
+        }
+        (AvroType::TimestampMicros, Value::DateTimeTz { value, .. }) => write_long(out, value.timestamp_micros()),
+        (AvroType::Boolean, Value::Boolean(b)) => out.push(b as u8),
+        (AvroType::Bytes, Value::Binary(bytes)) => write_bytes(out, &bytes),
+        (AvroType::String, value) => write_bytes(out, value.into_string().as_bytes()),
+        (_, value) => bail!("unexpected value {:?}", value),
+    }
+    Ok(())
+}
+
+/// long按zigzag变长编码
+fn write_long(out: &mut Vec<u8>, value: i64) {
+    let mut n = ((value << 1) ^ (value >> 63)) as u64;
+    while n >= 0x80 {
+        out.push((n as u8 & 0x7f) | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+/// bytes/string：长度后跟内容
+fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    write_long(out, bytes.len() as i64);
+    out.extend_from_slice(bytes);
+}
+
+/// decimal的未缩放值按最短的大端序补码表示
+fn decimal_bytes(value: i128) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let mut start = 0;
+    while start < bytes.len() - 1 {
+        let redundant = (bytes[start] == 0x00 && bytes[start + 1] & 0x80 == 0)
+            || (bytes[start] == 0xff && bytes[start + 1] & 0x80 != 0);
+        if !redundant {
+            break;
+        }
+        start += 1;
+    }
+    bytes[start..].to_vec()
+}
+
+/// 同步标记：由输出路径、当前时间和进程号生成的16个字节
+fn sync_marker(output_file: &str) -> [u8; 16] {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or_default();
+    let digest = Sha256::new()
+        .chain_update(output_file.as_bytes())
+        .chain_update(nanos.to_le_bytes())
+        .chain_update(std::process::id().to_le_bytes())
+        .finalize();
+    let mut sync = [0u8; 16];
+    sync.copy_from_slice(&digest[..16]);
+    sync
+}
@@ -0,0 +1,464 @@
+use crate::config::ConfigSource;
+use crate::layers::ConfigLayers;
+use clap::{Args, Parser, Subcommand};
+use toml::Value;
+
+#[derive(Parser)]
+#[command(name = "el")]
+#[command(about = "数据导出导入工具 - Data Export/Import Tool", long_about = None)]
+pub struct Cli {
+    /// 详细日志 (Verbose logging)
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+
+    #[command(subcommand)]
+    pub command: Commands,
+}
+
+#[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)]
+pub enum Commands {
+    /// 导出数据 (Export data)
+    Export {
+        #[command(flatten)]
+        connection: ConnectionArgs,
+
+        #[command(flatten)]
+        export: ExportArgs,
+
+        /// 试运行：校验配置、解析查询并输出执行计划，不导出数据 (Validate, describe and explain without exporting)
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// 预览查询结果 (Preview query results in the terminal)
+    Query {
+        #[command(flatten)]
+        connection: ConnectionArgs,
+
+        /// 查询SQL或SQL文件路径 (Query SQL or SQL file path)
+        sql: String,
+
+        /// 显示行数 (Number of rows to show)
+        #[arg(short = 'n', long, default_value = "20")]
+        limit: usize,
+
+        /// 单元格最大显示宽度 (Max cell display width)
+        #[arg(long, default_value = "40")]
+        max_width: usize,
+    },
+
+    /// 测试数据库连接并输出诊断信息 (Test connection and show diagnostics)
+    ///
+    /// 退出码 (Exit codes): 0=成功 (ok), 1=其他错误 (other), 2=认证失败 (auth),
+    /// 3=网络错误 (network), 4=缺少Oracle客户端 (missing Instant Client)
+    TestConnection {
+        #[command(flatten)]
+        connection: ConnectionArgs,
+    },
+
+    /// 配置管理 (Configuration commands)
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// 输出生效的配置及每项配置的来源 (Show effective config and where each value came from)
+    Show {
+        #[command(flatten)]
+        connection: ConnectionArgs,
+
+        #[command(flatten)]
+        export: ExportArgs,
+    },
+}
+
+/// 数据库连接参数，各子命令共用
+#[derive(Args)]
+pub struct ConnectionArgs {
+    /// 配置文件路径 (Config file path)
+    #[arg(short, long)]
+    pub config: Option<String>,
+
+    /// 数据库类型 (Database type: oracle/mysql/postgresql)
+    #[arg(long)]
+    db_type: Option<String>,
+
+    /// 数据库连接字符串 (Database connection string: host:port/service_name)
+    #[arg(long)]
+    conn: Option<String>,
+
+    /// 用户名 (Username)
+    #[arg(long)]
+    username: Option<String>,
+
+    /// 密码 (Password)
+    #[arg(long)]
+    password: Option<String>,
+
+    /// 批量获取大小 (Fetch size) [默认 default: 1000]
+    #[arg(long)]
+    fetch: Option<usize>,
+
+    /// LOB预取大小，字节 (LOB prefetch size in bytes)
+    #[arg(long)]
+    lob_prefetch: Option<u32>,
+}
+
+impl ConnectionArgs {
+    /// 将命令行参数写入命令行配置层
+    pub fn apply(&self, layers: &mut ConfigLayers) {
+        set_opt(layers, "database.db_type", "--db-type", self.db_type.clone());
+        set_opt(layers, "database.connection_string", "--conn", self.conn.clone());
+        set_opt(layers, "database.username", "--username", self.username.clone());
+        set_opt(layers, "database.password", "--password", self.password.clone());
+        set_opt(layers, "database.fetch_size", "--fetch", self.fetch.map(|v| v as i64));
+        set_opt(layers, "database.lob_prefetch_size", "--lob-prefetch", self.lob_prefetch.map(|v| v as i64));
+    }
+}
+
+/// 导出参数，未指定的参数使用配置文件、环境变量或默认值
+#[derive(Args)]
+pub struct ExportArgs {
+    /// 查询SQL或SQL文件路径 (Query SQL or SQL file path)
+    #[arg(long, conflicts_with = "table")]
+    query: Option<String>,
+
+    /// 按表导出，支持多个表和模式 (Export by table name: SCHEMA.TABLE, comma separated, % and ? wildcards)
+    #[arg(long, value_delimiter = ',')]
+    table: Vec<String>,
+
+    /// 表模式过滤条件 (WHERE clause for table mode)
+    #[arg(long = "where")]
+    where_clause: Option<String>,
+
+    /// 表模式导出的列 (Columns for table mode, comma separated)
+    #[arg(long, value_delimiter = ',')]
+    columns: Vec<String>,
+
+    /// 只导出这些列 (Output only these columns, comma separated)
+    #[arg(long, value_delimiter = ',')]
+    include: Vec<String>,
+
+    /// 不导出这些列 (Exclude these columns, comma separated)
+    #[arg(long, value_delimiter = ',')]
+    exclude: Vec<String>,
+
+    /// 输出列顺序 (Output column order, comma separated; unlisted columns follow)
+    #[arg(long, value_delimiter = ',')]
+    order: Vec<String>,
+
+    /// 表头中的输出列名 (Rename output columns: SOURCE=NAME, comma separated)
+    #[arg(long, value_delimiter = ',', value_parser = parse_rename)]
+    rename: Vec<(String, String)>,
+
+    /// 行过滤条件 (Row filter expression, e.g. "AMOUNT > 0 AND STATUS != 'X'")
+    #[arg(long)]
+    filter: Option<String>,
+
+    /// 计算列 (Computed column: NAME=EXPR, repeatable)
+    #[arg(long, value_parser = parse_computed)]
+    computed: Vec<(String, String)>,
+
+    /// 输出文件，`-` 表示标准输出，支持 s3://、webhdfs://、sftp:// 地址 (Output file, - for stdout, or s3/webhdfs/sftp URL)
+    #[arg(short, long)]
+    output: Option<String>,
+
+    /// 导出格式 (Export format: csv/tsv/custom/fixed/sql/xlsx/arrow/avro) [默认 default: csv]
+    #[arg(long)]
+    format: Option<String>,
+
+    /// 分隔符，支持转义序列和多字节分隔符 (Delimiter, escapes like \x01 or multi-byte like |~| allowed) [默认 default: \x03]
+    #[arg(long)]
+    delimiter: Option<String>,
+
+    /// 记录结束符，仅自定义及定长格式 (Record terminator for custom/fixed formats, e.g. \r\n or \x1e) [默认 default: \n]
+    #[arg(long)]
+    record_terminator: Option<String>,
+
+    /// 加引号规则 (Quote style: necessary/always/never/non-numeric) [默认 default: necessary]
+    #[arg(long)]
+    quote_style: Option<String>,
+
+    /// 引号字符 (Quote character) [默认 default: "]
+    #[arg(long)]
+    quote_char: Option<String>,
+
+    /// 转义字符，指定后引号写为转义字符加引号而非两个引号 (Escape character instead of doubled quotes, e.g. \\)
+    #[arg(long)]
+    escape_char: Option<String>,
+
+    /// 自定义格式下字段内分隔符/换行的处理 (Embedded delimiters/newlines for custom format: none/replace/strip) [默认 default: none]
+    #[arg(long)]
+    sanitize: Option<String>,
+
+    /// sanitize=replace 时的替换内容 (Replacement for sanitize=replace) [默认 default: " "]
+    #[arg(long)]
+    sanitize_replacement: Option<String>,
+
+    /// 输出文件编码 (Output encoding: utf-8/gbk/gb18030/latin1 ...) [默认 default: utf-8]
+    #[arg(long)]
+    encoding: Option<String>,
+
+    /// 无法编码的字符的处理 (Unmappable characters: error/replace/skip) [默认 default: error]
+    #[arg(long)]
+    unmappable: Option<String>,
+
+    /// CLOB/LONG最大字符数，超出截断 (Max CLOB/LONG length in characters)
+    #[arg(long)]
+    lob_max_length: Option<usize>,
+
+    /// BLOB/RAW输出方式 (BLOB/RAW output: base64/hex/file) [默认 default: base64]
+    #[arg(long)]
+    blob: Option<String>,
+
+    /// BLOB附属文件目录 (Directory for BLOB files, relative to the output file) [默认 default: lobs]
+    #[arg(long)]
+    lob_dir: Option<String>,
+
+    /// SQL格式的方言 (SQL dialect for INSERT statements: oracle/postgres/mysql) [默认 default: oracle]
+    #[arg(long)]
+    sql_dialect: Option<String>,
+
+    /// SQL格式的目标表名 (Target table for INSERT statements) [默认 default: 源表或输出文件名]
+    #[arg(long)]
+    sql_table: Option<String>,
+
+    /// 每条INSERT语句的行数 (Rows per INSERT statement) [默认 default: 100]
+    #[arg(long)]
+    sql_batch_size: Option<usize>,
+
+    /// Arrow格式的写入方式 (Arrow IPC layout: file/stream) [默认 default: file]
+    #[arg(long)]
+    arrow_mode: Option<String>,
+
+    /// Arrow记录批的行数 (Rows per Arrow record batch) [默认 default: fetch_size]
+    #[arg(long)]
+    arrow_batch_size: Option<usize>,
+
+    /// Avro数据块的压缩方式 (Avro block codec: null/deflate/snappy) [默认 default: null]
+    #[arg(long)]
+    avro_codec: Option<String>,
+
+    /// Avro数据块的字节数 (Avro block size in bytes before compression) [默认 default: 65536]
+    #[arg(long)]
+    avro_block_size: Option<usize>,
+
+    /// 定长格式中值超出列宽时的处理 (Fixed-width overflow policy: error/truncate) [默认 default: error]
+    #[arg(long)]
+    fixed_overflow: Option<String>,
+
+    /// 定长格式的填充字符 (Fixed-width pad character) [默认 default: 空格 space]
+    #[arg(long)]
+    fixed_pad: Option<String>,
+
+    /// S3兼容存储的服务地址，如MinIO (S3-compatible endpoint, e.g. http://localhost:9000)
+    #[arg(long)]
+    s3_endpoint: Option<String>,
+
+    /// S3区域 (S3 region) [默认 default: us-east-1]
+    #[arg(long)]
+    s3_region: Option<String>,
+
+    /// 写入UTF-8 BOM (Write UTF-8 BOM)
+    #[arg(long, overrides_with = "no_bom")]
+    bom: bool,
+
+    /// 不写入UTF-8 BOM (Do not write UTF-8 BOM)
+    #[arg(long, overrides_with = "bom")]
+    no_bom: bool,
+
+    /// 写入导出清单 (Write export manifest) [配置脱敏规则时总是写入]
+    #[arg(long, overrides_with = "no_manifest")]
+    manifest: bool,
+
+    /// 不写入导出清单 (Do not write export manifest)
+    #[arg(long, overrides_with = "manifest")]
+    no_manifest: bool,
+
+    /// 生成SQL*Loader控制文件 (Write SQL*Loader control file)
+    #[arg(long, overrides_with = "no_sqlldr")]
+    sqlldr: bool,
+
+    /// 不生成SQL*Loader控制文件 (Do not write SQL*Loader control file)
+    #[arg(long, overrides_with = "sqlldr")]
+    no_sqlldr: bool,
+
+    /// 显示进度 (Show progress)
+    #[arg(long, overrides_with = "no_progress")]
+    progress: bool,
+
+    /// 不显示进度 (Hide progress)
+    #[arg(long, overrides_with = "progress")]
+    no_progress: bool,
+
+    /// 包含表头 (Include header)
+    #[arg(long, overrides_with = "no_header")]
+    header: bool,
+
+    /// 不包含表头 (Exclude header)
+    #[arg(long, overrides_with = "header")]
+    no_header: bool,
+
+    /// 缓冲区大小（字节）(Buffer size in bytes) [默认 default: 1048576]
+    #[arg(long)]
+    buffer_size: Option<usize>,
+
+    /// 压缩类型 (Compression type: none/gzip) [默认 default: none]
+    #[arg(long)]
+    compression: Option<String>,
+
+    /// 日志文件路径 (Log file path, append mode)
+    #[arg(long)]
+    log_file: Option<String>,
+
+    /// 进度输出间隔（行数）(Progress output interval in rows) [默认 default: 1000000]
+    #[arg(long)]
+    progress_interval: Option<u64>,
+
+    /// 表结构附属文件 (Schema sidecar file: none/ddl/json) [默认 default: none]
+    #[arg(long)]
+    schema: Option<String>,
+
+    /// 一致性快照，导出开始时获取当前SCN (Consistent snapshot at current SCN)
+    #[arg(long, overrides_with = "no_snapshot")]
+    snapshot: bool,
+
+    /// 不使用一致性快照 (Disable consistent snapshot)
+    #[arg(long, overrides_with = "snapshot")]
+    no_snapshot: bool,
+
+    /// 闪回查询指定SCN (Flashback query as of SCN)
+    #[arg(long, conflicts_with = "as_of_timestamp", value_parser = parse_scn)]
+    as_of_scn: Option<i64>,
+
+    /// 闪回查询指定时间点 (Flashback query as of timestamp: YYYY-MM-DD HH24:MI:SS)
+    #[arg(long)]
+    as_of_timestamp: Option<String>,
+}
+
+impl ExportArgs {
+    /// 将命令行参数写入命令行配置层
+    pub fn apply(&self, layers: &mut ConfigLayers) {
+        // --query 与 --table 互斥，指定其一时清除配置文件中的另一项
+        if let Some(ref q) = self.query {
+            layers.set("export.query", q.clone(), ConfigSource::Cli("--query"));
+            layers.set("export.tables", Value::Array(Vec::new()), ConfigSource::Cli("--query"));
+        }
+        if !self.table.is_empty() {
+            layers.set("export.tables", self.table.clone(), ConfigSource::Cli("--table"));
+            layers.set("export.query", "", ConfigSource::Cli("--table"));
+        }
+        set_opt(layers, "export.where", "--where", self.where_clause.clone());
+        if !self.columns.is_empty() {
+            layers.set("export.table_columns", self.columns.clone(), ConfigSource::Cli("--columns"));
+        }
+        if !self.include.is_empty() {
+            layers.set("export.mapping.include", self.include.clone(), ConfigSource::Cli("--include"));
+        }
+        if !self.exclude.is_empty() {
+            layers.set("export.mapping.exclude", self.exclude.clone(), ConfigSource::Cli("--exclude"));
+        }
+        if !self.order.is_empty() {
+            layers.set("export.mapping.order", self.order.clone(), ConfigSource::Cli("--order"));
+        }
+        set_opt(layers, "export.filter", "--filter", self.filter.clone());
+        for (name, expr) in &self.computed {
+            layers.set(&format!("export.computed.{}", name), expr.clone(), ConfigSource::Cli("--computed"));
+        }
+        for (source, name) in &self.rename {
+            layers.set(&format!("export.mapping.rename.{}", source), name.clone(), ConfigSource::Cli("--rename"));
+        }
+        set_opt(layers, "export.output_file", "--output", self.output.clone());
+        set_opt(layers, "export.format", "--format", self.format.as_ref().map(|f| f.to_lowercase()));
+        set_opt(layers, "export.delimiter", "--delimiter", self.delimiter.clone());
+        set_opt(layers, "export.record_terminator", "--record-terminator", self.record_terminator.clone());
+        set_opt(layers, "export.quote_style", "--quote-style", self.quote_style.as_ref().map(|q| q.to_lowercase()));
+        set_opt(layers, "export.quote_char", "--quote-char", self.quote_char.clone());
+        set_opt(layers, "export.escape_char", "--escape-char", self.escape_char.clone());
+        set_opt(layers, "export.sanitize", "--sanitize", self.sanitize.as_ref().map(|s| s.to_lowercase()));
+        set_opt(layers, "export.sanitize_replacement", "--sanitize-replacement", self.sanitize_replacement.clone());
+        set_opt(layers, "export.encoding", "--encoding", self.encoding.clone());
+        set_opt(layers, "export.unmappable", "--unmappable", self.unmappable.as_ref().map(|u| u.to_lowercase()));
+        set_opt(layers, "export.lob.max_length", "--lob-max-length", self.lob_max_length.map(|v| v as i64));
+        set_opt(layers, "export.lob.blob", "--blob", self.blob.as_ref().map(|b| b.to_lowercase()));
+        set_opt(layers, "export.lob.dir", "--lob-dir", self.lob_dir.clone());
+        set_opt(layers, "export.sql.dialect", "--sql-dialect", self.sql_dialect.as_ref().map(|d| d.to_lowercase()));
+        set_opt(layers, "export.sql.table", "--sql-table", self.sql_table.clone());
+        set_opt(layers, "export.sql.batch_size", "--sql-batch-size", self.sql_batch_size.map(|v| v as i64));
+        set_opt(layers, "export.arrow.mode", "--arrow-mode", self.arrow_mode.as_ref().map(|m| m.to_lowercase()));
+        set_opt(layers, "export.arrow.batch_size", "--arrow-batch-size", self.arrow_batch_size.map(|v| v as i64));
+        set_opt(layers, "export.avro.codec", "--avro-codec", self.avro_codec.as_ref().map(|c| c.to_lowercase()));
+        set_opt(layers, "export.avro.block_size", "--avro-block-size", self.avro_block_size.map(|v| v as i64));
+        set_opt(layers, "export.fixed.overflow", "--fixed-overflow", self.fixed_overflow.as_ref().map(|o| o.to_lowercase()));
+        set_opt(layers, "export.fixed.pad", "--fixed-pad", self.fixed_pad.clone());
+        set_opt(layers, "export.s3.endpoint", "--s3-endpoint", self.s3_endpoint.clone());
+        set_opt(layers, "export.s3.region", "--s3-region", self.s3_region.clone());
+        set_flag(layers, "export.bom", "--bom", "--no-bom", self.bom, self.no_bom);
+        set_flag(layers, "export.manifest", "--manifest", "--no-manifest", self.manifest, self.no_manifest);
+        set_flag(layers, "export.sqlldr", "--sqlldr", "--no-sqlldr", self.sqlldr, self.no_sqlldr);
+        set_flag(layers, "export.show_progress", "--progress", "--no-progress", self.progress, self.no_progress);
+        set_flag(layers, "export.include_header", "--header", "--no-header", self.header, self.no_header);
+        set_opt(layers, "export.buffer_size", "--buffer-size", self.buffer_size.map(|v| v as i64));
+        set_opt(layers, "export.compression", "--compression", self.compression.as_ref().map(|c| c.to_lowercase()));
+        set_opt(layers, "export.progress_interval", "--progress-interval", self.progress_interval.map(|v| v as i64));
+        set_opt(layers, "export.schema_output", "--schema", self.schema.as_ref().map(|s| s.to_lowercase()));
+        set_flag(layers, "export.snapshot", "--snapshot", "--no-snapshot", self.snapshot, self.no_snapshot);
+
+        // 命令行指定的时间点覆盖配置文件中的时间点
+        if let Some(scn) = self.as_of_scn {
+            layers.set("export.as_of_scn", scn, ConfigSource::Cli("--as-of-scn"));
+            layers.unset("export.as_of_timestamp", ConfigSource::Cli("--as-of-scn"));
+        }
+        if let Some(ref ts) = self.as_of_timestamp {
+            layers.set("export.as_of_timestamp", ts.clone(), ConfigSource::Cli("--as-of-timestamp"));
+            layers.unset("export.as_of_scn", ConfigSource::Cli("--as-of-timestamp"));
+        }
+
+        set_opt(layers, "logging.log_file", "--log-file", self.log_file.clone());
+    }
+}
+
+/// 解析 `SOURCE=NAME` 形式的重命名参数
+fn parse_rename(input: &str) -> Result<(String, String), String> {
+    match input.split_once('=') {
+        Some((source, name)) if !source.trim().is_empty() && !name.trim().is_empty() => {
+            Ok((source.trim().to_string(), name.trim().to_string()))
+        }
+        _ => Err(format!("expected SOURCE=NAME, got {:?}", input)),
+    }
+}
+
+/// 解析SCN参数，SCN为非负数且需能以 `i64` 保存
+fn parse_scn(input: &str) -> Result<i64, String> {
+    let scn: u64 = input.trim().parse().map_err(|e| format!("invalid SCN {:?}: {}", input, e))?;
+    i64::try_from(scn).map_err(|_| format!("SCN {} exceeds the maximum of {}", scn, i64::MAX))
+}
+
+/// 解析 `NAME=EXPR` 形式的计算列参数，表达式中可以包含逗号和等号
+fn parse_computed(input: &str) -> Result<(String, String), String> {
+    match input.split_once('=') {
+        Some((name, expr)) if !name.trim().is_empty() && !expr.trim().is_empty() => {
+            Ok((name.trim().to_string(), expr.trim().to_string()))
+        }
+        _ => Err(format!("expected NAME=EXPR, got {:?}", input)),
+    }
+}
+
+fn set_opt<T: Into<Value>>(layers: &mut ConfigLayers, key: &str, flag: &'static str, value: Option<T>) {
+    if let Some(v) = value {
+        layers.set(key, v, ConfigSource::Cli(flag));
+    }
+}
+
+/// 成对的开关参数（如 --header / --no-header），均未指定时不覆盖
+fn set_flag(layers: &mut ConfigLayers, key: &str, on_flag: &'static str, off_flag: &'static str, on: bool, off: bool) {
+    if on {
+        layers.set(key, true, ConfigSource::Cli(on_flag));
+    } else if off {
+        layers.set(key, false, ConfigSource::Cli(off_flag));
+    }
+}
@@ -1,8 +1,10 @@
+use crate::table::TableRef;
 use serde::{Deserialize, Serialize};
-use std::fs;
-use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fmt;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     pub database: DatabaseConfig,
     pub export: ExportConfig,
@@ -11,6 +13,7 @@ pub struct Config {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct LoggingConfig {
     #[serde(default)]
     pub log_file: Option<String>,
@@ -18,6 +21,7 @@ pub struct LoggingConfig {
     pub verbose: bool,
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for LoggingConfig {
     fn default() -> Self {
         Self {
@@ -28,22 +32,105 @@ impl Default for LoggingConfig {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct DatabaseConfig {
     pub db_type: String,
     pub connection_string: String,
     pub username: String,
     pub password: String,
-    #[serde(default)]
+    #[serde(default = "default_fetch_size")]
     pub fetch_size: usize,
+    /// 每个LOB定位符的预取大小（字节），较小的LOB随行数据一起返回，减少往返次数
+    #[serde(default)]
+    pub lob_prefetch_size: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct ExportConfig {
+    /// 查询SQL（表模式下可省略）
+    #[serde(default)]
     pub query: String,
+    /// 输出文件（表模式下默认为表名），`-` 表示写入标准输出；也可以是已创建的命名管道，
+    /// 或 `s3://`、`webhdfs://`、`swebhdfs://`、`sftp://` 地址
+    #[serde(default)]
     pub output_file: String,
     pub format: ExportFormat,
+    /// 字段分隔符，支持转义序列 (如 `\x01\x02`、`|~|`)
     #[serde(default = "default_delimiter")]
     pub delimiter: String,
+    /// 记录结束符（仅自定义格式），支持转义序列 (如 `\r\n`、`\x1e`)
+    #[serde(default = "default_record_terminator")]
+    pub record_terminator: String,
+    /// 字段加引号的规则
+    #[serde(default)]
+    pub quote_style: QuoteStyle,
+    /// 引号字符，支持转义序列
+    #[serde(default = "default_quote_char")]
+    pub quote_char: String,
+    /// 转义字符：指定后字段内的引号写为 `<转义字符><引号>`，否则写为两个引号
+    #[serde(default)]
+    pub escape_char: Option<String>,
+    /// 自定义格式下字段内出现分隔符、记录结束符或换行时的处理方式
+    #[serde(default)]
+    pub sanitize: SanitizeMode,
+    /// `sanitize = "replace"` 时的替换内容，支持转义序列
+    #[serde(default = "default_sanitize_replacement")]
+    pub sanitize_replacement: String,
+    /// 输出文件编码 (如 utf-8、gbk、gb18030、latin1)
+    #[serde(default = "default_encoding")]
+    pub encoding: String,
+    /// 目标编码无法表示的字符的处理方式
+    #[serde(default)]
+    pub unmappable: UnmappableMode,
+    /// 在UTF-8文件开头写入BOM（便于Excel识别编码）
+    #[serde(default)]
+    pub bom: bool,
+    /// 按列名或列类型的格式化规则
+    #[serde(default)]
+    pub columns: ColumnsConfig,
+    /// 行过滤条件，如 `AMOUNT > 0 AND STATUS != 'X'`
+    #[serde(default)]
+    pub filter: Option<String>,
+    /// 计算列：列名 => 表达式，追加在查询结果列之后
+    #[serde(default)]
+    pub computed: BTreeMap<String, String>,
+    /// 输出列的选择、排序及重命名
+    #[serde(default)]
+    pub mapping: ColumnMapping,
+    /// CLOB/BLOB/LONG列的处理方式
+    #[serde(default)]
+    pub lob: LobConfig,
+    /// 按列的脱敏规则
+    #[serde(default)]
+    pub masking: MaskingConfig,
+    /// 导出完成后写入清单文件（配置了脱敏时总是写入）
+    #[serde(default)]
+    pub manifest: bool,
+    /// 同时生成SQL*Loader控制文件 (`.ctl`)
+    #[serde(default)]
+    pub sqlldr: bool,
+    /// SQL格式（INSERT语句）的选项
+    #[serde(default)]
+    pub sql: SqlConfig,
+    /// Arrow格式的选项
+    #[serde(default)]
+    pub arrow: ArrowConfig,
+    /// Avro格式的选项
+    #[serde(default)]
+    pub avro: AvroConfig,
+    /// 定长格式的选项
+    #[serde(default)]
+    pub fixed: FixedConfig,
+    /// 写入S3兼容对象存储的选项
+    #[serde(default)]
+    pub s3: S3Config,
+    /// 通过WebHDFS写入HDFS的选项
+    #[serde(default)]
+    pub webhdfs: WebHdfsConfig,
+    /// 通过SFTP写入的选项
+    #[serde(default)]
+    pub sftp: SftpConfig,
     #[serde(default)]
     pub show_progress: bool,
     #[serde(default)]
@@ -54,6 +141,30 @@ pub struct ExportConfig {
     pub compression: CompressionType,
     #[serde(default = "default_progress_interval")]
     pub progress_interval: u64,
+    /// 导出开始时获取当前SCN，所有查询读取同一时间点的数据
+    #[serde(default)]
+    pub snapshot: bool,
+    /// 闪回查询指定SCN
+    #[serde(default)]
+    pub as_of_scn: Option<u64>,
+    /// 闪回查询指定时间点 (格式: YYYY-MM-DD HH24:MI:SS)
+    #[serde(default)]
+    pub as_of_timestamp: Option<String>,
+    /// 表模式：按表名导出，支持 SCHEMA.TABLE 和LIKE模式 (如 HR.EMP%)
+    #[serde(default)]
+    pub tables: Vec<String>,
+    /// 表模式的过滤条件
+    #[serde(default, rename = "where")]
+    pub where_clause: Option<String>,
+    /// 表模式导出的列（默认全部列）
+    #[serde(default)]
+    pub table_columns: Vec<String>,
+    /// 表结构附属文件类型
+    #[serde(default)]
+    pub schema_output: SchemaOutput,
+    /// 表模式下当前导出的源表（由表模式自动填充）
+    #[serde(skip)]
+    pub source_table: Option<TableRef>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,6 +173,18 @@ pub enum ExportFormat {
     Csv,
     Tsv,
     Custom,
+    /// INSERT语句脚本
+    Sql,
+    /// Excel工作簿
+    Xlsx,
+    /// Apache Arrow IPC文件（Feather v2）或IPC流
+    #[serde(rename = "arrow", alias = "feather")]
+    ArrowIpc,
+    /// Apache Avro对象容器文件
+    Avro,
+    /// 定长记录，各列按固定宽度填充
+    #[serde(rename = "fixed", alias = "fixed_width")]
+    FixedWidth,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -71,16 +194,569 @@ pub enum CompressionType {
     Gzip,
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for CompressionType {
     fn default() -> Self {
         CompressionType::None
     }
 }
 
+/// 字段加引号的规则
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum QuoteStyle {
+    /// 仅在字段包含分隔符、引号或换行时加引号
+    #[default]
+    Necessary,
+    /// 所有字段都加引号
+    Always,
+    /// 从不加引号（原样输出）
+    Never,
+    /// 非数字字段加引号
+    NonNumeric,
+}
+
+/// 字段内特殊字符（分隔符、记录结束符、换行）的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SanitizeMode {
+    /// 保持原样
+    #[default]
+    None,
+    /// 替换为 `sanitize_replacement`
+    Replace,
+    /// 删除
+    Strip,
+}
+
+/// 目标编码无法表示的字符的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnmappableMode {
+    /// 报错并停止导出
+    #[default]
+    Error,
+    /// 替换为 `?`
+    Replace,
+    /// 跳过该字符
+    Skip,
+}
+
+/// `[export.columns]` 格式化规则，列名规则中的配置项优先于类型规则
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ColumnsConfig {
+    /// 按列类型（date、timestamp、timestamp_tz、number、integer、float、text、boolean）
+    #[serde(default)]
+    pub types: BTreeMap<String, ColumnFormat>,
+    /// 按列名（不区分大小写）
+    #[serde(default)]
+    pub names: BTreeMap<String, ColumnFormat>,
+}
+
+/// `[export.mapping]` 输出列映射，列名不区分大小写
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ColumnMapping {
+    /// 只导出这些列（默认全部列）
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// 不导出这些列
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// 输出顺序，未列出的列按查询结果中的顺序排在后面
+    #[serde(default)]
+    pub order: Vec<String>,
+    /// 表头中的输出列名：源列名 => 输出列名
+    #[serde(default)]
+    pub rename: BTreeMap<String, String>,
+}
+
+/// `[export.lob]` 大对象及二进制列的处理
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LobConfig {
+    /// CLOB/NCLOB/LONG的最大字符数，超出部分截断并追加 `truncation_marker`（默认不限制）
+    #[serde(default)]
+    pub max_length: Option<usize>,
+    /// 截断标记
+    #[serde(default = "default_truncation_marker")]
+    pub truncation_marker: String,
+    /// BLOB/RAW/LONG RAW的输出方式
+    #[serde(default)]
+    pub blob: BlobMode,
+    /// `blob = "file"` 时附属文件的目录（相对于输出文件所在目录），支持 `{table}` 占位符
+    #[serde(default = "default_lob_dir")]
+    pub dir: String,
+}
+
+impl Default for LobConfig {
+    fn default() -> Self {
+        Self {
+            max_length: None,
+            truncation_marker: default_truncation_marker(),
+            blob: BlobMode::Base64,
+            dir: default_lob_dir(),
+        }
+    }
+}
+
+/// 二进制列的输出方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlobMode {
+    /// 行内Base64
+    #[default]
+    Base64,
+    /// 行内十六进制（大写）
+    Hex,
+    /// 写入附属文件 `<dir>/<行号>_<列名>.bin`，字段中为文件路径
+    File,
+}
+
+/// `[export.sql]` INSERT语句的生成方式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SqlConfig {
+    /// 目标数据库的SQL方言
+    #[serde(default)]
+    pub dialect: SqlDialect,
+    /// 目标表名，可带模式名（默认为源表，查询模式下取输出文件名）
+    #[serde(default)]
+    pub table: Option<String>,
+    /// 每条INSERT语句包含的行数
+    #[serde(default = "default_sql_batch_size")]
+    pub batch_size: usize,
+}
+
+impl Default for SqlConfig {
+    fn default() -> Self {
+        Self {
+            dialect: SqlDialect::Oracle,
+            table: None,
+            batch_size: default_sql_batch_size(),
+        }
+    }
+}
+
+/// INSERT语句的SQL方言
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SqlDialect {
+    /// 多行时使用 `INSERT ALL ... SELECT 1 FROM DUAL`，脚本按SQL*Plus执行
+    #[default]
+    Oracle,
+    /// 多行 `VALUES (...), (...)`
+    Postgres,
+    /// 多行 `VALUES (...), (...)`，字符串中的反斜杠需转义
+    Mysql,
+}
+
+/// `[export.arrow]` Arrow IPC输出
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ArrowConfig {
+    /// 写入IPC文件（随机访问，即Feather v2）或IPC流（可边写边读）
+    #[serde(default)]
+    pub mode: ArrowMode,
+    /// 每个记录批次的行数（默认与 `fetch_size` 一致）
+    #[serde(default)]
+    pub batch_size: Option<usize>,
+    /// 未指定精度的NUMBER列（包括聚合和计算列）的类型
+    #[serde(default)]
+    pub number: ArrowNumber,
+    /// `number = "decimal"` 时的小数位数，超出的小数位四舍五入
+    #[serde(default = "default_arrow_number_scale")]
+    pub number_scale: u8,
+}
+
+impl Default for ArrowConfig {
+    fn default() -> Self {
+        Self {
+            mode: ArrowMode::default(),
+            batch_size: None,
+            number: ArrowNumber::default(),
+            number_scale: default_arrow_number_scale(),
+        }
+    }
+}
+
+/// 未指定精度的NUMBER列在Arrow中的类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArrowNumber {
+    /// Decimal128(38, number_scale)
+    #[default]
+    Decimal,
+    /// 十进制文本，不损失精度
+    String,
+    /// 双精度浮点数，超过15位有效数字时损失精度
+    Float64,
+}
+
+/// Arrow IPC的写入方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArrowMode {
+    #[default]
+    File,
+    Stream,
+}
+
+/// `[export.avro]` Avro对象容器文件输出
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AvroConfig {
+    /// 数据块的压缩方式
+    #[serde(default)]
+    pub codec: AvroCodec,
+    /// 数据块的大小（压缩前的字节数），达到后写出一个数据块
+    #[serde(default = "default_avro_block_size")]
+    pub block_size: usize,
+    /// schema中的记录名（默认为源表名，查询模式下为输出文件名）
+    #[serde(default)]
+    pub name: Option<String>,
+    /// schema的命名空间（默认为源表所属用户，查询模式下不设置）
+    #[serde(default)]
+    pub namespace: Option<String>,
+}
+
+impl Default for AvroConfig {
+    fn default() -> Self {
+        Self {
+            codec: AvroCodec::default(),
+            block_size: default_avro_block_size(),
+            name: None,
+            namespace: None,
+        }
+    }
+}
+
+/// Avro数据块的压缩方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AvroCodec {
+    #[default]
+    Null,
+    Deflate,
+    Snappy,
+}
+
+/// `[export.fixed]` 定长格式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FixedConfig {
+    /// 值超过列宽时的处理
+    #[serde(default)]
+    pub overflow: OverflowPolicy,
+    /// 默认的填充字符（单个ASCII字符）
+    #[serde(default = "default_fixed_pad")]
+    pub pad: String,
+    /// 按输出列名（不区分大小写）配置宽度、对齐方式及填充字符，未配置宽度的列按列定义推算
+    #[serde(default)]
+    pub columns: BTreeMap<String, FixedColumn>,
+}
+
+impl Default for FixedConfig {
+    fn default() -> Self {
+        Self {
+            overflow: OverflowPolicy::default(),
+            pad: default_fixed_pad(),
+            columns: BTreeMap::new(),
+        }
+    }
+}
+
+/// 定长格式的单列设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FixedColumn {
+    /// 列宽（输出编码下的字节数）
+    #[serde(default)]
+    pub width: Option<usize>,
+    /// 对齐方式（默认数字右对齐，其他左对齐）
+    #[serde(default)]
+    pub align: Option<Alignment>,
+    /// 填充字符（覆盖 `[export.fixed] pad`）
+    #[serde(default)]
+    pub pad: Option<String>,
+    /// 超出列宽时的处理（覆盖 `[export.fixed] overflow`）
+    #[serde(default)]
+    pub overflow: Option<OverflowPolicy>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Alignment {
+    Left,
+    Right,
+}
+
+/// 值超过列宽时的处理
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverflowPolicy {
+    /// 报错并终止导出
+    #[default]
+    Error,
+    /// 截断到列宽
+    Truncate,
+}
+
+/// `[export.s3]` S3兼容对象存储（`s3://bucket/key`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct S3Config {
+    /// 服务地址，如MinIO的 `http://localhost:9000`（默认为 `https://s3.<region>.amazonaws.com`）
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    #[serde(default = "default_s3_region")]
+    pub region: String,
+    /// 使用路径形式的地址 `<endpoint>/<bucket>/<key>`（默认在配置了endpoint时使用）
+    #[serde(default)]
+    pub path_style: Option<bool>,
+    /// 访问密钥ID（默认读取环境变量 AWS_ACCESS_KEY_ID）
+    #[serde(default)]
+    pub access_key_id: Option<String>,
+    /// 访问密钥（默认读取环境变量 AWS_SECRET_ACCESS_KEY）
+    #[serde(default)]
+    pub secret_access_key: Option<String>,
+    /// 从该环境变量读取访问密钥，优先于 `secret_access_key`
+    #[serde(default)]
+    pub secret_access_key_env: Option<String>,
+    /// 分段上传的分段大小（字节），不小于5 MiB；数据不超过一个分段时直接上传
+    #[serde(default = "default_s3_part_size")]
+    pub part_size: usize,
+}
+
+impl Default for S3Config {
+    fn default() -> Self {
+        Self {
+            endpoint: None,
+            region: default_s3_region(),
+            path_style: None,
+            access_key_id: None,
+            secret_access_key: None,
+            secret_access_key_env: None,
+            part_size: default_s3_part_size(),
+        }
+    }
+}
+
+/// `[export.webhdfs]` WebHDFS（`webhdfs://namenode:9870/path`，HTTPS为 `swebhdfs://`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebHdfsConfig {
+    /// 以该用户写入（`user.name`，默认读取环境变量 HADOOP_USER_NAME）
+    #[serde(default)]
+    pub user: Option<String>,
+    /// 每次追加写入的数据大小（字节）
+    #[serde(default = "default_webhdfs_chunk_size")]
+    pub chunk_size: usize,
+}
+
+impl Default for WebHdfsConfig {
+    fn default() -> Self {
+        Self {
+            user: None,
+            chunk_size: default_webhdfs_chunk_size(),
+        }
+    }
+}
+
+/// `[export.sftp]` SFTP（`sftp://user@host:22/path`），依次尝试私钥、密码及ssh-agent认证
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SftpConfig {
+    /// 私钥文件
+    #[serde(default)]
+    pub key_file: Option<String>,
+    /// 从该环境变量读取密码（或私钥的口令）
+    #[serde(default)]
+    pub password_env: Option<String>,
+    /// known_hosts文件（默认为 `~/.ssh/known_hosts`）
+    #[serde(default)]
+    pub known_hosts: Option<String>,
+    /// 主机不在known_hosts中时仍然连接（不校验主机密钥，仅用于测试环境）
+    #[serde(default)]
+    pub accept_unknown_hosts: bool,
+}
+
+/// `[export.masking]` 脱敏配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MaskingConfig {
+    /// 哈希、令牌及伪造值使用的盐值
+    #[serde(default)]
+    pub salt: Option<String>,
+    /// 从该环境变量读取盐值，优先于 `salt`，避免将盐值写入配置文件
+    #[serde(default)]
+    pub salt_env: Option<String>,
+    /// 按输出列（源列名，不区分大小写）的脱敏规则
+    #[serde(default)]
+    pub columns: BTreeMap<String, MaskRule>,
+}
+
+/// 单列的脱敏规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MaskRule {
+    pub method: MaskMethod,
+    /// partial: 保留开头的字符数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_start: Option<usize>,
+    /// partial: 保留结尾的字符数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_end: Option<usize>,
+    /// partial: 遮盖字符 (默认 `*`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mask_char: Option<String>,
+    /// token: 令牌前缀 (默认 `TKN_`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    /// token: 令牌的十六进制字符数 (默认16，最大64)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub length: Option<usize>,
+}
+
+/// 脱敏方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MaskMethod {
+    /// 加盐SHA-256
+    Sha256,
+    /// HMAC-SHA256，以盐值为密钥
+    Hmac,
+    /// 部分遮盖，如 `138****1234`
+    Partial,
+    /// 置为NULL
+    Null,
+    /// 保留格式的伪造值
+    Fake,
+    /// 确定性令牌
+    Token,
+}
+
+/// 单列的格式化规则，未指定的项保持默认输出
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ColumnFormat {
+    /// 日期/时间戳格式 (strftime，如 `%Y-%m-%d %H:%M:%S%.3f`)
+    #[serde(default)]
+    pub format: Option<String>,
+    /// 数字保留的有效位数
+    #[serde(default)]
+    pub precision: Option<u32>,
+    /// 数字固定的小数位数（四舍五入，不足补零）
+    #[serde(default)]
+    pub scale: Option<u32>,
+    /// 数字整数部分的千位分隔符
+    #[serde(default)]
+    pub thousands_separator: Option<String>,
+    /// 数字的小数点
+    #[serde(default)]
+    pub decimal_separator: Option<String>,
+    /// 去除首尾空白
+    #[serde(default)]
+    pub trim: Option<TrimMode>,
+    /// 布尔值（或Y/N、1/0等标志值）为真时的输出
+    #[serde(default)]
+    pub true_value: Option<String>,
+    /// 布尔值（或Y/N、1/0等标志值）为假时的输出
+    #[serde(default)]
+    pub false_value: Option<String>,
+}
+
+/// 去除空白的位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrimMode {
+    Left,
+    Right,
+    Both,
+}
+
+/// 表结构附属文件类型
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SchemaOutput {
+    #[default]
+    None,
+    /// 源表的DDL (DBMS_METADATA)，描述完整的表而不是导出的列
+    Ddl,
+    /// 可移植的JSON结构描述
+    Json,
+}
+
+/// 一致性快照时间点
+#[derive(Debug, Clone)]
+pub enum SnapshotPoint {
+    /// 导出开始时的当前SCN
+    Current,
+    Scn(u64),
+    Timestamp(String),
+}
+
+fn default_fetch_size() -> usize {
+    1000
+}
+
 fn default_delimiter() -> String {
     "\x03".to_string()
 }
 
+fn default_record_terminator() -> String {
+    "\n".to_string()
+}
+
+fn default_quote_char() -> String {
+    "\"".to_string()
+}
+
+fn default_sanitize_replacement() -> String {
+    " ".to_string()
+}
+
+fn default_encoding() -> String {
+    "utf-8".to_string()
+}
+
+fn default_truncation_marker() -> String {
+    "...[truncated]".to_string()
+}
+
+fn default_lob_dir() -> String {
+    "lobs".to_string()
+}
+
+fn default_arrow_number_scale() -> u8 {
+    10
+}
+
+fn default_sql_batch_size() -> usize {
+    100
+}
+
+fn default_avro_block_size() -> usize {
+    64 * 1024
+}
+
+fn default_fixed_pad() -> String {
+    " ".to_string()
+}
+
+fn default_s3_region() -> String {
+    "us-east-1".to_string()
+}
+
+fn default_s3_part_size() -> usize {
+    8 * 1024 * 1024
+}
+
+fn default_webhdfs_chunk_size() -> usize {
+    64 * 1024 * 1024
+}
+
 fn default_buffer_size() -> usize {
     1024 * 1024  // 1MB
 }
@@ -93,18 +769,102 @@ impl Default for DatabaseConfig {
     fn default() -> Self {
         Self {
             db_type: "oracle".to_string(),
-            connection_string: "localhost:1521/ORCL".to_string(),
+            connection_string: String::new(),
             username: String::new(),
             password: String::new(),
-            fetch_size: 1000,
+            fetch_size: default_fetch_size(),
+            lob_prefetch_size: None,
         }
     }
 }
 
-impl Config {
-    pub fn from_file(path: &str) -> Result<Self> {
-        let content = fs::read_to_string(path)?;
-        let config: Config = toml::from_str(&content)?;
-        Ok(config)
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            query: String::new(),
+            output_file: String::new(),
+            format: ExportFormat::Csv,
+            delimiter: default_delimiter(),
+            record_terminator: default_record_terminator(),
+            quote_style: QuoteStyle::Necessary,
+            quote_char: default_quote_char(),
+            escape_char: None,
+            sanitize: SanitizeMode::None,
+            sanitize_replacement: default_sanitize_replacement(),
+            encoding: default_encoding(),
+            unmappable: UnmappableMode::Error,
+            bom: false,
+            columns: ColumnsConfig::default(),
+            filter: None,
+            computed: BTreeMap::new(),
+            mapping: ColumnMapping::default(),
+            lob: LobConfig::default(),
+            masking: MaskingConfig::default(),
+            manifest: false,
+            sqlldr: false,
+            sql: SqlConfig::default(),
+            arrow: ArrowConfig::default(),
+            avro: AvroConfig::default(),
+            fixed: FixedConfig::default(),
+            s3: S3Config::default(),
+            webhdfs: WebHdfsConfig::default(),
+            sftp: SftpConfig::default(),
+            show_progress: false,
+            include_header: false,
+            buffer_size: default_buffer_size(),
+            compression: CompressionType::None,
+            progress_interval: default_progress_interval(),
+            snapshot: false,
+            as_of_scn: None,
+            as_of_timestamp: None,
+            tables: Vec::new(),
+            where_clause: None,
+            table_columns: Vec::new(),
+            schema_output: SchemaOutput::None,
+            source_table: None,
+        }
+    }
+}
+
+/// 配置项的来源，用于错误提示
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    Default,
+    File(String),
+    Env(String),
+    Cli(&'static str),
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigSource::Default => write!(f, "default"),
+            ConfigSource::File(path) => write!(f, "config file {}", path),
+            ConfigSource::Env(var) => write!(f, "env {}", var),
+            ConfigSource::Cli(flag) => write!(f, "command line {}", flag),
+        }
+    }
+}
+
+/// 表示标准输出的输出文件名
+pub const STDOUT_FILE: &str = "-";
+
+impl ExportConfig {
+    /// 是否写入标准输出
+    pub fn writes_to_stdout(&self) -> bool {
+        self.output_file == STDOUT_FILE
+    }
+
+    /// 根据配置确定快照时间点，指定SCN优先于时间戳
+    pub fn snapshot_point(&self) -> Option<SnapshotPoint> {
+        if let Some(scn) = self.as_of_scn {
+            Some(SnapshotPoint::Scn(scn))
+        } else if let Some(ref ts) = self.as_of_timestamp {
+            Some(SnapshotPoint::Timestamp(ts.clone()))
+        } else if self.snapshot {
+            Some(SnapshotPoint::Current)
+        } else {
+            None
+        }
     }
 }
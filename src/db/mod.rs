@@ -1,15 +1,22 @@
 pub mod oracle;
 
 use anyhow::Result;
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDateTime, Timelike};
+use serde::Serialize;
+use std::fmt;
+use std::path::PathBuf;
 
 pub trait Database {
     fn connect(&mut self) -> Result<()>;
-    fn execute_query(&mut self, query: &str) -> Result<QueryResult>;
+    /// 执行查询并返回结果，`limit` 限制最多读取的行数
+    fn execute_query(&mut self, query: &str, limit: Option<usize>) -> Result<QueryResult>;
 }
 
 pub struct QueryResult {
-    pub columns: Vec<String>,
+    pub columns: Vec<ColumnInfo>,
     pub rows: Vec<Vec<String>>,
+    /// 受limit限制，还有未读取的行
+    pub has_more: bool,
 }
 
 impl QueryResult {
@@ -17,6 +24,146 @@ impl QueryResult {
         Self {
             columns: Vec::new(),
             rows: Vec::new(),
+            has_more: false,
         }
     }
 }
+
+/// `blob = "file"` 时按列将BLOB流式写入附属文件目录下的临时文件
+pub struct BlobSpool {
+    pub dir: PathBuf,
+    /// 按查询结果列：是否写入临时文件
+    pub columns: Vec<bool>,
+}
+
+/// 与数据库无关的列类型分类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnKind {
+    Text,
+    Integer,
+    Number,
+    Float,
+    Date,
+    Timestamp,
+    TimestampTz,
+    Interval,
+    Binary,
+    Clob,
+    Blob,
+    Boolean,
+    Other,
+}
+
+impl ColumnKind {
+    /// 配置中使用的类型名称，与序列化名称一致
+    pub fn name(self) -> &'static str {
+        match self {
+            ColumnKind::Text => "text",
+            ColumnKind::Integer => "integer",
+            ColumnKind::Number => "number",
+            ColumnKind::Float => "float",
+            ColumnKind::Date => "date",
+            ColumnKind::Timestamp => "timestamp",
+            ColumnKind::TimestampTz => "timestamp_tz",
+            ColumnKind::Interval => "interval",
+            ColumnKind::Binary => "binary",
+            ColumnKind::Clob => "clob",
+            ColumnKind::Blob => "blob",
+            ColumnKind::Boolean => "boolean",
+            ColumnKind::Other => "other",
+        }
+    }
+
+    pub fn is_numeric(self) -> bool {
+        matches!(self, ColumnKind::Integer | ColumnKind::Number | ColumnKind::Float)
+    }
+
+    pub fn is_temporal(self) -> bool {
+        matches!(self, ColumnKind::Date | ColumnKind::Timestamp | ColumnKind::TimestampTz)
+    }
+}
+
+/// 带类型的字段值，供导出时按列类型格式化
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Text(String),
+    /// 数字的十进制文本表示，保留数据库返回的全部精度
+    Number(String),
+    /// 日期/时间戳，`precision` 为秒的小数位数
+    DateTime { value: NaiveDateTime, precision: u8 },
+    /// 带时区的时间戳
+    DateTimeTz { value: DateTime<FixedOffset>, precision: u8 },
+    Boolean(bool),
+    /// BLOB/RAW/LONG RAW的原始字节
+    Binary(Vec<u8>),
+    /// 已直接写入附属文件目录下临时文件的BLOB（`blob = "file"`），内容不读入内存
+    BlobFile(PathBuf),
+}
+
+impl Value {
+    /// 转换为默认文本表示，文本值不复制
+    pub fn into_string(self) -> String {
+        match self {
+            Value::Text(s) | Value::Number(s) => s,
+            other => other.to_string(),
+        }
+    }
+}
+
+/// 默认文本表示：NULL为空字符串，日期为 `YYYY-MM-DD HH24:MI:SS[.FF]`，布尔值为 TRUE/FALSE，
+/// 二进制为大写十六进制（与Oracle的RAW转换一致）
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Null => Ok(()),
+            Value::Text(s) | Value::Number(s) => f.write_str(s),
+            Value::DateTime { value, precision } => write_datetime(f, value, *precision),
+            Value::DateTimeTz { value, precision } => {
+                write_datetime(f, &value.naive_local(), *precision)?;
+                let offset = value.offset().local_minus_utc();
+                let sign = if offset < 0 { '-' } else { '+' };
+                let offset = offset.abs();
+                write!(f, " {}{:02}:{:02}", sign, offset / 3600, offset % 3600 / 60)
+            }
+            Value::Boolean(b) => f.write_str(if *b { "TRUE" } else { "FALSE" }),
+            Value::Binary(bytes) => bytes.iter().try_for_each(|b| write!(f, "{:02X}", b)),
+            Value::BlobFile(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+fn write_datetime(f: &mut fmt::Formatter, value: &NaiveDateTime, precision: u8) -> fmt::Result {
+    write!(
+        f,
+        "{}-{:02}-{:02} {:02}:{:02}:{:02}",
+        value.year(),
+        value.month(),
+        value.day(),
+        value.hour(),
+        value.minute(),
+        value.second()
+    )?;
+    if (1..=9).contains(&precision) {
+        let digits = precision as usize;
+        let fraction = value.nanosecond() / 10u32.pow(9 - precision as u32);
+        write!(f, ".{:0width$}", fraction, width = digits)?;
+    }
+    Ok(())
+}
+
+/// 查询结果的列元数据
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnInfo {
+    pub name: String,
+    /// 数据库原生类型，如 NUMBER(10,2)
+    #[serde(rename = "type")]
+    pub type_name: String,
+    pub kind: ColumnKind,
+    pub precision: Option<u32>,
+    pub scale: Option<i32>,
+    /// 字符/二进制类型的最大长度
+    pub size: Option<u32>,
+    pub nullable: bool,
+}
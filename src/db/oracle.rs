@@ -1,11 +1,76 @@
-use crate::config::DatabaseConfig;
-use crate::db::{Database, QueryResult};
+use crate::config::{DatabaseConfig, LobConfig, SnapshotPoint};
+use crate::db::{BlobSpool, ColumnInfo, ColumnKind, Database, QueryResult, Value};
+use crate::table::{TableRef, TableSpec};
 use anyhow::{Context, Result};
-use oracle::{Connection, Row};
+use chrono::{FixedOffset, NaiveDate, TimeZone};
+use oracle::oci_attr::DefaultLobPrefetchSize;
+use oracle::sql_type::{Blob, Clob, Nclob, OracleType, Timestamp};
+use oracle::{Connection, Row, Version};
+use std::io::{self, Read};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// 连接失败原因分类，用于 test-connection 返回不同的退出码
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectFailure {
+    /// 用户名/密码错误、账户锁定或密码过期
+    Auth,
+    /// 监听、服务名或网络不可达
+    Network,
+    /// 未找到Oracle Instant Client
+    ClientLibrary,
+    Other,
+}
+
+impl ConnectFailure {
+    pub fn classify(err: &anyhow::Error) -> Self {
+        let Some(oracle_err) = err.downcast_ref::<oracle::Error>() else {
+            return ConnectFailure::Other;
+        };
+
+        if let Some(code) = oracle_err.dpi_code() {
+            // DPI-1047: 无法加载客户端库; DPI-1072: 客户端版本不受支持
+            return match code {
+                1047 | 1072 => ConnectFailure::ClientLibrary,
+                _ => ConnectFailure::Other,
+            };
+        }
+
+        match oracle_err.oci_code() {
+            Some(1017 | 1005 | 1045 | 28000 | 28001 | 28009 | 28040) => ConnectFailure::Auth,
+            // ORA-12xxx 为TNS/Net错误
+            Some(3113 | 3114 | 3135 | 12150..=12999) => ConnectFailure::Network,
+            _ => ConnectFailure::Other,
+        }
+    }
+
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ConnectFailure::Other => 1,
+            ConnectFailure::Auth => 2,
+            ConnectFailure::Network => 3,
+            ConnectFailure::ClientLibrary => 4,
+        }
+    }
+}
+
+/// 连接诊断信息
+pub struct ConnectionDiagnostics {
+    pub server_version: String,
+    pub server_banner: String,
+    pub client_version: String,
+    pub nls_characterset: String,
+    pub nls_nchar_characterset: String,
+    pub session_time_zone: String,
+    pub session_user: String,
+    pub current_schema: String,
+    pub round_trip: Duration,
+}
 
 pub struct OracleDatabase {
     config: DatabaseConfig,
     connection: Option<Connection>,
+    snapshot_scn: Option<u64>,
 }
 
 impl OracleDatabase {
@@ -13,9 +78,20 @@ impl OracleDatabase {
         Self {
             config,
             connection: None,
+            snapshot_scn: None,
         }
     }
 
+    /// 每次往返获取的行数
+    pub fn fetch_size(&self) -> usize {
+        self.config.fetch_size
+    }
+
+    /// 当前会话的快照SCN（未开启快照时为None）
+    pub fn snapshot_scn(&self) -> Option<u64> {
+        self.snapshot_scn
+    }
+
     fn build_connection_string(&self) -> String {
         // connection_string格式: host:port/service_name
         // 添加//前缀以符合Oracle连接字符串格式
@@ -33,7 +109,251 @@ impl OracleDatabase {
         Ok(values)
     }
 
-    pub fn get_column_info(&mut self, query: &str) -> Result<Vec<String>> {
+    /// 按列类型读取带类型的值，供导出时格式化；
+    /// 配置了 `max_length` 时CLOB分段读取，超长内容不会整体读入内存
+    fn row_to_values(
+        &self,
+        row: &Row,
+        types: &[OracleType],
+        kinds: &[ColumnKind],
+        lob: &LobConfig,
+        spool: Option<&BlobSpool>,
+    ) -> Result<Vec<Value>> {
+        let mut values = Vec::with_capacity(kinds.len());
+
+        for (i, (oracle_type, kind)) in types.iter().zip(kinds).enumerate() {
+            let value = match oracle_type {
+                OracleType::CLOB => row.get::<_, Option<Clob>>(i)?.map(|clob| read_text(clob, lob)).transpose()?.map(Value::Text),
+                OracleType::NCLOB => row.get::<_, Option<Nclob>>(i)?.map(|clob| read_text(clob, lob)).transpose()?.map(Value::Text),
+                OracleType::Long => row.get::<_, Option<String>>(i)?.map(|text| Value::Text(truncate_text(text, lob))),
+                OracleType::BLOB => match spool.filter(|spool| spool.columns.get(i).copied().unwrap_or(false)) {
+                    Some(spool) => row.get::<_, Option<Blob>>(i)?.map(|blob| spool_binary(blob, &spool.dir)).transpose()?,
+                    None => row.get::<_, Option<Blob>>(i)?.map(read_binary).transpose()?.map(Value::Binary),
+                },
+                OracleType::BFILE | OracleType::Raw(_) | OracleType::LongRaw => row.get::<_, Option<Vec<u8>>>(i)?.map(Value::Binary),
+                _ => match kind {
+                    ColumnKind::Integer | ColumnKind::Number | ColumnKind::Float => {
+                        row.get::<_, Option<String>>(i)?.map(Value::Number)
+                    }
+                    ColumnKind::Date | ColumnKind::Timestamp | ColumnKind::TimestampTz => row
+                        .get::<_, Option<Timestamp>>(i)?
+                        .map(|ts| timestamp_to_value(&ts))
+                        .transpose()?,
+                    ColumnKind::Boolean => row.get::<_, Option<bool>>(i)?.map(Value::Boolean),
+                    _ => row.get::<_, Option<String>>(i)?.map(Value::Text),
+                },
+            };
+            values.push(value.unwrap_or(Value::Null));
+        }
+
+        Ok(values)
+    }
+
+    /// 收集服务器/客户端版本、字符集、会话信息及往返延迟
+    pub fn diagnostics(&mut self) -> Result<ConnectionDiagnostics> {
+        let conn = self
+            .connection
+            .as_ref()
+            .context("Database not connected")?;
+
+        let (server_version, server_banner) = conn.server_version()?;
+        let client_version = Version::client()?;
+
+        // 多次ping取平均值作为往返延迟
+        const PINGS: u32 = 5;
+        let start = Instant::now();
+        for _ in 0..PINGS {
+            conn.ping()?;
+        }
+        let round_trip = start.elapsed() / PINGS;
+
+        let (session_user, current_schema, session_time_zone) = conn.query_row_as::<(String, String, String)>(
+            "SELECT SYS_CONTEXT('USERENV', 'SESSION_USER'), \
+             SYS_CONTEXT('USERENV', 'CURRENT_SCHEMA'), SESSIONTIMEZONE FROM dual",
+            &[],
+        )?;
+
+        let (nls_characterset, nls_nchar_characterset) = conn.query_row_as::<(String, String)>(
+            "SELECT MAX(DECODE(parameter, 'NLS_CHARACTERSET', value)), \
+             MAX(DECODE(parameter, 'NLS_NCHAR_CHARACTERSET', value)) FROM nls_database_parameters",
+            &[],
+        )?;
+
+        Ok(ConnectionDiagnostics {
+            server_version: server_version.to_string(),
+            server_banner,
+            client_version: client_version.to_string(),
+            nls_characterset,
+            nls_nchar_characterset,
+            session_time_zone,
+            session_user,
+            current_schema,
+            round_trip,
+        })
+    }
+
+    /// 将快照时间点解析为SCN
+    pub fn resolve_snapshot_scn(&mut self, point: &SnapshotPoint) -> Result<u64> {
+        let scn = match point {
+            SnapshotPoint::Scn(scn) => *scn,
+            SnapshotPoint::Current => {
+                let result = self.execute_query("SELECT DBMS_FLASHBACK.GET_SYSTEM_CHANGE_NUMBER FROM dual", Some(1))?;
+                let value = result
+                    .rows
+                    .first()
+                    .and_then(|row| row.first())
+                    .context("Failed to get current SCN")?;
+                value.parse::<u64>()
+                    .with_context(|| format!("Invalid SCN value: {}", value))?
+            }
+            SnapshotPoint::Timestamp(ts) => {
+                let conn = self
+                    .connection
+                    .as_ref()
+                    .context("Database not connected")?;
+                conn.query_row_as::<u64>(
+                    "SELECT TIMESTAMP_TO_SCN(TO_TIMESTAMP(:1, 'YYYY-MM-DD HH24:MI:SS')) FROM dual",
+                    &[ts],
+                )
+                .with_context(|| format!("Failed to convert timestamp to SCN: {}", ts))?
+            }
+        };
+
+        Ok(scn)
+    }
+
+    /// 开启会话级闪回查询，之后本会话的所有查询都读取同一SCN的数据
+    pub fn begin_snapshot(&mut self, point: &SnapshotPoint) -> Result<u64> {
+        let scn = self.resolve_snapshot_scn(point)?;
+
+        let conn = self
+            .connection
+            .as_ref()
+            .context("Database not connected")?;
+        conn.execute("BEGIN DBMS_FLASHBACK.ENABLE_AT_SYSTEM_CHANGE_NUMBER(:1); END;", &[&scn])
+            .with_context(|| format!("Failed to enable flashback query at SCN {}", scn))?;
+
+        self.snapshot_scn = Some(scn);
+        Ok(scn)
+    }
+
+    /// 从数据字典解析表名，支持LIKE模式，未指定schema时使用当前schema
+    pub fn resolve_tables(&mut self, spec: &TableSpec) -> Result<Vec<TableRef>> {
+        let conn = self
+            .connection
+            .as_ref()
+            .context("Database not connected")?;
+
+        let owner = match spec.owner {
+            Some(ref owner) => owner.clone(),
+            None => conn.current_schema()?,
+        };
+
+        // 两种方式都查询ALL_TAB_COLUMNS，表和视图均可按名称或模式导出
+        let (sql, name) = if spec.is_pattern() {
+            (
+                "SELECT owner, table_name FROM all_tab_columns \
+                 WHERE owner = :1 AND table_name LIKE :2 ESCAPE '\\' AND column_id = 1 ORDER BY table_name",
+                spec.like_pattern(),
+            )
+        } else {
+            (
+                "SELECT owner, table_name FROM all_tab_columns \
+                 WHERE owner = :1 AND table_name = :2 AND column_id = 1",
+                spec.name.clone(),
+            )
+        };
+
+        let rows = conn.query_as::<(String, String)>(sql, &[&owner, &name])?;
+        let mut tables = Vec::new();
+        for row in rows {
+            let (owner, name) = row?;
+            tables.push(TableRef { owner, name });
+        }
+
+        Ok(tables)
+    }
+
+    /// 按列顺序获取表的列名
+    pub fn table_columns(&mut self, table: &TableRef) -> Result<Vec<String>> {
+        let conn = self
+            .connection
+            .as_ref()
+            .context("Database not connected")?;
+
+        let rows = conn.query_as::<String>(
+            "SELECT column_name FROM all_tab_columns \
+             WHERE owner = :1 AND table_name = :2 ORDER BY column_id",
+            &[&table.owner, &table.name],
+        )?;
+
+        let mut columns = Vec::new();
+        for row in rows {
+            columns.push(row?);
+        }
+
+        Ok(columns)
+    }
+
+    /// 使用DBMS_METADATA获取表的DDL
+    pub fn table_ddl(&mut self, table: &TableRef) -> Result<String> {
+        let conn = self
+            .connection
+            .as_ref()
+            .context("Database not connected")?;
+
+        let ddl = conn
+            .query_row_as::<String>(
+                "SELECT DBMS_METADATA.GET_DDL('TABLE', :1, :2) FROM dual",
+                &[&table.name, &table.owner],
+            )
+            .with_context(|| format!("Failed to get DDL for table {}.{}", table.owner, table.name))?;
+
+        Ok(ddl.trim().to_string())
+    }
+
+    /// 仅解析查询获取列信息，通过 `WHERE 1 = 0` 包装避免读取数据
+    pub fn describe_query(&mut self, query: &str) -> Result<Vec<ColumnInfo>> {
+        let wrapped = format!("SELECT * FROM ({}) WHERE 1 = 0", query);
+        self.get_column_info(&wrapped)
+            .context("Failed to parse query")
+    }
+
+    /// 获取查询的执行计划及优化器估算的行数
+    pub fn explain_plan(&mut self, query: &str) -> Result<(Vec<String>, Option<u64>)> {
+        let conn = self
+            .connection
+            .as_ref()
+            .context("Database not connected")?;
+
+        const STATEMENT_ID: &str = "EL_DRY_RUN";
+        conn.execute(
+            &format!("EXPLAIN PLAN SET STATEMENT_ID = '{}' FOR {}", STATEMENT_ID, query),
+            &[],
+        )
+        .context("Failed to explain query")?;
+
+        let rows = conn.query_as::<Option<String>>(
+            "SELECT plan_table_output FROM TABLE(DBMS_XPLAN.DISPLAY('PLAN_TABLE', :1, 'TYPICAL'))",
+            &[&STATEMENT_ID],
+        )?;
+        let mut plan = Vec::new();
+        for row in rows {
+            plan.push(row?.unwrap_or_default());
+        }
+
+        let estimated_rows = conn.query_row_as::<Option<u64>>(
+            "SELECT MAX(cardinality) FROM plan_table WHERE statement_id = :1 AND id = 0",
+            &[&STATEMENT_ID],
+        )?;
+
+        // 执行计划写入plan_table，回滚以免残留
+        conn.rollback()?;
+
+        Ok((plan, estimated_rows))
+    }
+
+    pub fn get_column_info(&mut self, query: &str) -> Result<Vec<ColumnInfo>> {
         let conn = self
             .connection
             .as_ref()
@@ -45,18 +365,25 @@ impl OracleDatabase {
 
         let rows = stmt.query(&[])?;
         
-        let columns: Vec<String> = rows
+        let columns: Vec<ColumnInfo> = rows
             .column_info()
             .iter()
-            .map(|col| col.name().to_string())
+            .map(to_column_info)
             .collect();
 
         Ok(columns)
     }
 
-    pub fn execute_query_streaming<F>(&mut self, query: &str, mut callback: F) -> Result<Vec<String>>
+    /// 流式读取查询结果，LOB列通过定位符读取并按 `lob` 配置截断
+    pub fn execute_query_streaming<F>(
+        &mut self,
+        query: &str,
+        lob: &LobConfig,
+        spool: Option<&BlobSpool>,
+        mut callback: F,
+    ) -> Result<Vec<String>>
     where
-        F: FnMut(Vec<String>) -> Result<()>,
+        F: FnMut(Vec<Value>) -> Result<()>,
     {
         let conn = self
             .connection
@@ -65,6 +392,7 @@ impl OracleDatabase {
 
         let mut stmt = conn.statement(query)
             .fetch_array_size(self.config.fetch_size as u32)
+            .lob_locator()
             .build()?;
 
         let rows = stmt.query(&[])?;
@@ -74,12 +402,20 @@ impl OracleDatabase {
             .iter()
             .map(|col| col.name().to_string())
             .collect();
-
-        let col_count = columns.len();
+        let types: Vec<OracleType> = rows
+            .column_info()
+            .iter()
+            .map(|col| col.oracle_type().clone())
+            .collect();
+        let kinds: Vec<ColumnKind> = rows
+            .column_info()
+            .iter()
+            .map(|col| to_column_info(col).kind)
+            .collect();
 
         for row_result in rows {
             let row = row_result?;
-            let values = self.row_to_strings(&row, col_count)?;
+            let values = self.row_to_values(&row, &types, &kinds, lob, spool)?;
             callback(values)?;
         }
 
@@ -87,33 +423,155 @@ impl OracleDatabase {
     }
 }
 
+/// LOB分段读取的缓冲区大小
+const LOB_CHUNK_SIZE: usize = 64 * 1024;
+
+/// 读取CLOB内容；限制长度时只读取判断是否超长所需的部分
+fn read_text<R: Read>(mut reader: R, lob: &LobConfig) -> Result<String> {
+    let Some(max_length) = lob.max_length else {
+        let mut text = String::new();
+        reader.read_to_string(&mut text).context("Failed to read CLOB")?;
+        return Ok(text);
+    };
+
+    // UTF-8字符最多4字节，读取 (max_length + 1) * 4 字节即可判断是否超长
+    let limit = max_length.saturating_add(1).saturating_mul(4);
+    let mut buf = Vec::new();
+    let mut chunk = vec![0u8; LOB_CHUNK_SIZE];
+    while buf.len() < limit {
+        let n = reader.read(&mut chunk).context("Failed to read CLOB")?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    let text = String::from_utf8(buf).context("CLOB data is not valid UTF-8")?;
+    Ok(truncate_text(text, lob))
+}
+
+/// 超过 `max_length` 个字符时截断并追加截断标记
+fn truncate_text(mut text: String, lob: &LobConfig) -> String {
+    if let Some(max_length) = lob.max_length
+        && let Some((end, _)) = text.char_indices().nth(max_length)
+    {
+        text.truncate(end);
+        text.push_str(&lob.truncation_marker);
+    }
+    text
+}
+
+fn read_binary<R: Read>(mut reader: R) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).context("Failed to read BLOB")?;
+    Ok(bytes)
+}
+
+/// 将BLOB分段复制到附属文件目录下的临时文件，由LobWriter按行号重命名
+fn spool_binary<R: Read>(mut reader: R, dir: &Path) -> Result<Value> {
+    let mut file = tempfile::Builder::new()
+        .prefix(".lob-")
+        .tempfile_in(dir)
+        .with_context(|| format!("Failed to create LOB file in {}", dir.display()))?;
+    io::copy(&mut reader, &mut file).context("Failed to read BLOB")?;
+    let path = file.into_temp_path().keep().context("Failed to keep LOB file")?;
+    Ok(Value::BlobFile(path))
+}
+
+/// 将Oracle时间戳转换为日期时间值，保留小数秒精度及时区
+fn timestamp_to_value(ts: &Timestamp) -> Result<Value> {
+    let value = NaiveDate::from_ymd_opt(ts.year(), ts.month(), ts.day())
+        .and_then(|date| date.and_hms_nano_opt(ts.hour(), ts.minute(), ts.second(), ts.nanosecond()))
+        .with_context(|| format!("Invalid date value: {}", ts))?;
+    let precision = ts.precision();
+
+    if !ts.with_tz() {
+        return Ok(Value::DateTime { value, precision });
+    }
+    let value = FixedOffset::east_opt(ts.tz_offset())
+        .and_then(|offset| offset.from_local_datetime(&value).single())
+        .with_context(|| format!("Invalid time zone offset: {}", ts))?;
+    Ok(Value::DateTimeTz { value, precision })
+}
+
+/// 将Oracle列信息转换为通用列元数据
+fn to_column_info(col: &oracle::ColumnInfo) -> ColumnInfo {
+    let oracle_type = col.oracle_type();
+    let (kind, precision, scale, size) = match *oracle_type {
+        OracleType::Varchar2(n) | OracleType::NVarchar2(n) | OracleType::Char(n) | OracleType::NChar(n) => {
+            (ColumnKind::Text, None, None, Some(n))
+        }
+        OracleType::Rowid | OracleType::Long | OracleType::Json | OracleType::Xml => {
+            (ColumnKind::Text, None, None, None)
+        }
+        OracleType::Raw(n) => (ColumnKind::Binary, None, None, Some(n)),
+        OracleType::LongRaw => (ColumnKind::Binary, None, None, None),
+        OracleType::BinaryFloat | OracleType::BinaryDouble => (ColumnKind::Float, None, None, None),
+        // NUMBER的精度为0时表示未指定精度(38)
+        OracleType::Number(0, _) => (ColumnKind::Number, None, None, None),
+        OracleType::Number(p, 0) => (ColumnKind::Integer, Some(p as u32), Some(0), None),
+        OracleType::Number(p, s) => (ColumnKind::Number, Some(p as u32), Some(s as i32), None),
+        OracleType::Float(p) => (ColumnKind::Number, Some(p as u32), None, None),
+        OracleType::Int64 | OracleType::UInt64 => (ColumnKind::Integer, None, Some(0), None),
+        OracleType::Date => (ColumnKind::Date, None, None, None),
+        OracleType::Timestamp(fs) => (ColumnKind::Timestamp, None, Some(fs as i32), None),
+        OracleType::TimestampTZ(fs) | OracleType::TimestampLTZ(fs) => {
+            (ColumnKind::TimestampTz, None, Some(fs as i32), None)
+        }
+        OracleType::IntervalDS(_, _) | OracleType::IntervalYM(_) => (ColumnKind::Interval, None, None, None),
+        OracleType::CLOB | OracleType::NCLOB => (ColumnKind::Clob, None, None, None),
+        OracleType::BLOB | OracleType::BFILE => (ColumnKind::Blob, None, None, None),
+        OracleType::Boolean => (ColumnKind::Boolean, None, None, None),
+        _ => (ColumnKind::Other, None, None, None),
+    };
+
+    ColumnInfo {
+        name: col.name().to_string(),
+        type_name: oracle_type.to_string(),
+        kind,
+        precision,
+        scale,
+        size,
+        nullable: col.nullable(),
+    }
+}
+
 impl Database for OracleDatabase {
     fn connect(&mut self) -> Result<()> {
         let conn_str = self.build_connection_string();
-        let conn = Connection::connect(
+        let mut conn = Connection::connect(
             &self.config.username,
             &self.config.password,
             &conn_str,
         )
         .context("Failed to connect to Oracle database")?;
 
+        if let Some(size) = self.config.lob_prefetch_size {
+            conn.set_oci_attr::<DefaultLobPrefetchSize>(&size)
+                .context("Failed to set LOB prefetch size")?;
+        }
+
         self.connection = Some(conn);
         Ok(())
     }
 
-    fn execute_query(&mut self, query: &str) -> Result<QueryResult> {
+    fn execute_query(&mut self, query: &str, limit: Option<usize>) -> Result<QueryResult> {
         let conn = self
             .connection
             .as_ref()
             .context("Database not connected")?;
 
-        let mut stmt = conn.statement(query).build()?;
+        let mut builder = conn.statement(query);
+        if let Some(limit) = limit {
+            // 多取一行用于判断是否还有剩余数据
+            builder.fetch_array_size((limit + 1).min(self.config.fetch_size.max(1)) as u32);
+        }
+        let mut stmt = builder.build()?;
         let rows = stmt.query(&[])?;
         
-        let columns: Vec<String> = rows
+        let columns: Vec<ColumnInfo> = rows
             .column_info()
             .iter()
-            .map(|col| col.name().to_string())
+            .map(to_column_info)
             .collect();
         
         let col_count = columns.len();
@@ -122,6 +580,10 @@ impl Database for OracleDatabase {
         result.columns = columns;
         
         for row_result in rows {
+            if limit.is_some_and(|limit| result.rows.len() >= limit) {
+                result.has_more = true;
+                break;
+            }
             let row = row_result?;
             let values = self.row_to_strings(&row, col_count)?;
             result.rows.push(values);
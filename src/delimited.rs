@@ -0,0 +1,230 @@
+use crate::config::{ExportConfig, ExportFormat, QuoteStyle, SanitizeMode};
+use crate::encoding::Transcoder;
+use anyhow::{bail, Result};
+use std::io::Write;
+
+/// 分隔文本写入器，分隔符和记录结束符可以是任意字节序列
+///
+/// 默认按CSV规则在必要时加引号，字段内的引号写为两个引号；引号规则、引号字符、
+/// 转义字符及特殊字符的替换/删除均可配置。字段内容按输出编码转换，分隔符、结束符及引号按字节原样写出。
+pub struct DelimitedWriter {
+    delimiter: Vec<u8>,
+    terminator: Vec<u8>,
+    quote_style: QuoteStyle,
+    quote: u8,
+    escape: Option<u8>,
+    sanitize: SanitizeMode,
+    replacement: Vec<u8>,
+    transcoder: Option<Transcoder>,
+    buffer: Vec<u8>,
+    field: Vec<u8>,
+}
+
+impl DelimitedWriter {
+    /// 根据导出格式创建写入器：TSV固定使用制表符，分隔符和结束符仅对CSV/自定义格式生效
+    pub fn from_config(config: &ExportConfig) -> Result<Self> {
+        let (delimiter, terminator) = match config.format {
+            ExportFormat::Tsv => (b"\t".to_vec(), b"\n".to_vec()),
+            ExportFormat::Custom => (
+                parse_escapes(&config.delimiter)?,
+                parse_escapes(&config.record_terminator)?,
+            ),
+            _ => (parse_escapes(&config.delimiter)?, b"\n".to_vec()),
+        };
+        let quote = parse_single_byte(&config.quote_char)?;
+        let escape = config.escape_char.as_deref().map(parse_single_byte).transpose()?;
+
+        Ok(Self {
+            delimiter,
+            terminator,
+            quote_style: config.quote_style,
+            quote,
+            escape,
+            sanitize: config.sanitize,
+            replacement: parse_escapes(&config.sanitize_replacement)?,
+            transcoder: Transcoder::new(&config.encoding, config.unmappable)?,
+            buffer: Vec::with_capacity(1024),
+            field: Vec::with_capacity(256),
+        })
+    }
+
+    pub fn write_row(&mut self, writer: &mut dyn Write, values: &[String]) -> Result<()> {
+        self.buffer.clear();
+        for (i, value) in values.iter().enumerate() {
+            if i > 0 {
+                self.buffer.extend_from_slice(&self.delimiter);
+            }
+
+            self.field.clear();
+            self.sanitize_into_field(value.as_bytes());
+
+            let quoted = match self.quote_style {
+                QuoteStyle::Always => true,
+                QuoteStyle::Never => false,
+                QuoteStyle::NonNumeric => !is_numeric(&self.field),
+                // 单个空字段的记录需要加引号，否则与空行无法区分
+                QuoteStyle::Necessary => self.needs_quotes() || (values.len() == 1 && self.field.is_empty()),
+            };
+            if quoted {
+                self.buffer.push(self.quote);
+                let mut start = 0;
+                for (i, &b) in self.field.iter().enumerate() {
+                    let prefix = match self.escape {
+                        Some(escape) if b == self.quote || b == escape => escape,
+                        None if b == self.quote => self.quote,
+                        _ => continue,
+                    };
+                    push_text(self.transcoder.as_ref(), &self.field[start..i], &mut self.buffer)?;
+                    self.buffer.push(prefix);
+                    self.buffer.push(b);
+                    start = i + 1;
+                }
+                push_text(self.transcoder.as_ref(), &self.field[start..], &mut self.buffer)?;
+                self.buffer.push(self.quote);
+            } else {
+                push_text(self.transcoder.as_ref(), &self.field, &mut self.buffer)?;
+            }
+        }
+        self.buffer.extend_from_slice(&self.terminator);
+        writer.write_all(&self.buffer)?;
+        Ok(())
+    }
+
+    /// 按sanitize配置处理字段中的分隔符、记录结束符和换行，结果写入 `self.field`
+    fn sanitize_into_field(&mut self, value: &[u8]) {
+        if self.sanitize == SanitizeMode::None {
+            self.field.extend_from_slice(value);
+            return;
+        }
+
+        let mut rest = value;
+        while let Some(&b) = rest.first() {
+            let matched = if rest.starts_with(&self.delimiter) {
+                self.delimiter.len()
+            } else if rest.starts_with(&self.terminator) {
+                self.terminator.len()
+            } else if rest.starts_with(b"\r\n") {
+                2
+            } else if b == b'\r' || b == b'\n' {
+                1
+            } else {
+                0
+            };
+
+            if matched == 0 {
+                self.field.push(b);
+                rest = &rest[1..];
+            } else {
+                if self.sanitize == SanitizeMode::Replace {
+                    self.field.extend_from_slice(&self.replacement);
+                }
+                rest = &rest[matched..];
+            }
+        }
+    }
+
+    fn needs_quotes(&self) -> bool {
+        self.field
+            .iter()
+            .any(|&b| b == self.quote || b == b'\r' || b == b'\n' || Some(b) == self.escape)
+            || contains(&self.field, &self.delimiter)
+            || contains(&self.field, &self.terminator)
+    }
+}
+
+/// 写出字段内容，需要时转换编码
+fn push_text(transcoder: Option<&Transcoder>, text: &[u8], out: &mut Vec<u8>) -> Result<()> {
+    match transcoder {
+        Some(transcoder) => transcoder.encode(&String::from_utf8_lossy(text), out),
+        None => {
+            out.extend_from_slice(text);
+            Ok(())
+        }
+    }
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    !needle.is_empty() && haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+/// 判断字段是否为数字（可选符号、整数/小数部分、可选指数）
+pub fn is_numeric(field: &[u8]) -> bool {
+    let s = field.strip_prefix(b"-").or_else(|| field.strip_prefix(b"+")).unwrap_or(field);
+    let (mantissa, exponent) = match s.iter().position(|&b| b == b'e' || b == b'E') {
+        Some(pos) => (&s[..pos], Some(&s[pos + 1..])),
+        None => (s, None),
+    };
+
+    let mut parts = mantissa.splitn(2, |&b| b == b'.');
+    let int_part = parts.next().unwrap_or_default();
+    let frac_part = parts.next().unwrap_or_default();
+    let digits = |p: &[u8]| p.iter().all(u8::is_ascii_digit);
+    if int_part.is_empty() && frac_part.is_empty() || !digits(int_part) || !digits(frac_part) {
+        return false;
+    }
+
+    match exponent {
+        None => true,
+        Some(e) => {
+            let e = e.strip_prefix(b"-").or_else(|| e.strip_prefix(b"+")).unwrap_or(e);
+            !e.is_empty() && digits(e)
+        }
+    }
+}
+
+/// 解析必须为单个字节的配置（如引号字符、转义字符）
+pub fn parse_single_byte(input: &str) -> Result<u8> {
+    // 单独的反斜杠没有歧义，直接视为反斜杠本身
+    if input == "\\" {
+        return Ok(b'\\');
+    }
+    match parse_escapes(input)?.as_slice() {
+        [b] => Ok(*b),
+        _ => bail!("must be a single byte, got {:?}", input),
+    }
+}
+
+/// 解析转义序列，支持 `\t` `\r` `\n` `\0` `\\` `\xHH`（单个字节）和 `\uXXXX`（Unicode字符）
+pub fn parse_escapes(input: &str) -> Result<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(input.len());
+    let mut chars = input.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            let mut buf = [0u8; 4];
+            bytes.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+
+        match chars.next() {
+            Some('t') => bytes.push(b'\t'),
+            Some('r') => bytes.push(b'\r'),
+            Some('n') => bytes.push(b'\n'),
+            Some('0') => bytes.push(0),
+            Some('\\') => bytes.push(b'\\'),
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                match u8::from_str_radix(&hex, 16) {
+                    Ok(b) if is_hex(&hex, 2) => bytes.push(b),
+                    _ => bail!("invalid escape \\x{} (expected two hex digits)", hex),
+                }
+            }
+            Some('u') => {
+                let hex: String = chars.by_ref().take(4).collect();
+                match u32::from_str_radix(&hex, 16).ok().filter(|_| is_hex(&hex, 4)).and_then(char::from_u32) {
+                    Some(c) => {
+                        let mut buf = [0u8; 4];
+                        bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                    }
+                    None => bail!("invalid escape \\u{} (expected four hex digits)", hex),
+                }
+            }
+            Some(other) => bail!("unknown escape \\{}", other),
+            None => bail!("trailing backslash (use \\\\ for a literal backslash)"),
+        }
+    }
+    Ok(bytes)
+}
+
+fn is_hex(s: &str, len: usize) -> bool {
+    s.len() == len && s.chars().all(|c| c.is_ascii_hexdigit())
+}
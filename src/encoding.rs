@@ -0,0 +1,83 @@
+use crate::config::UnmappableMode;
+use anyhow::{bail, Result};
+use encoding_rs::{EncoderResult, Encoding, UTF_8};
+
+/// UTF-8 BOM
+pub const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// UTF-8中单个字符的最大字节数
+pub const UTF8_MAX_CHAR_BYTES: usize = 4;
+
+/// 按名称查找输出编码（如 `gbk`、`gb18030`、`latin1`），只接受可以写出的编码
+pub fn lookup(label: &str) -> Result<&'static Encoding> {
+    let Some(encoding) = Encoding::for_label(label.trim().as_bytes()) else {
+        bail!("unknown encoding {:?}", label);
+    };
+    // UTF-16等编码只能读取，encoding_rs写出时会改用UTF-8
+    if encoding.output_encoding() != encoding {
+        bail!("encoding {} cannot be used for output", encoding.name());
+    }
+    Ok(encoding)
+}
+
+/// 将UTF-8文本转换为目标编码
+pub struct Transcoder {
+    encoding: &'static Encoding,
+    unmappable: UnmappableMode,
+}
+
+impl Transcoder {
+    /// 目标编码为UTF-8时无需转换，返回None
+    pub fn new(label: &str, unmappable: UnmappableMode) -> Result<Option<Self>> {
+        let encoding = lookup(label)?;
+        if encoding == UTF_8 {
+            return Ok(None);
+        }
+        Ok(Some(Self { encoding, unmappable }))
+    }
+
+    /// 目标编码中单个字符的最大字节数（ISO-2022-JP按切换字符集的转义序列计入）
+    pub fn max_char_bytes(&self) -> usize {
+        if self.encoding.is_single_byte() {
+            return 1;
+        }
+        match self.encoding.name() {
+            "gb18030" => 4,
+            "EUC-JP" => 3,
+            "ISO-2022-JP" => 5,
+            _ => 2,
+        }
+    }
+
+    /// 转换文本并追加到输出缓冲区，无法映射的字符按unmappable配置处理
+    pub fn encode(&self, text: &str, out: &mut Vec<u8>) -> Result<()> {
+        let mut encoder = self.encoding.new_encoder();
+        let mut src = text;
+        loop {
+            let start = out.len();
+            let capacity = encoder
+                .max_buffer_length_from_utf8_without_replacement(src.len())
+                .unwrap_or(src.len() * 4 + 16);
+            out.resize(start + capacity, 0);
+            let (result, read, written) =
+                encoder.encode_from_utf8_without_replacement(src, &mut out[start..], true);
+            out.truncate(start + written);
+            src = &src[read..];
+
+            match result {
+                EncoderResult::InputEmpty => return Ok(()),
+                EncoderResult::OutputFull => {}
+                EncoderResult::Unmappable(ch) => match self.unmappable {
+                    UnmappableMode::Error => bail!(
+                        "character {:?} (U+{:04X}) cannot be represented in {} (set unmappable = \"replace\" or \"skip\")",
+                        ch,
+                        ch as u32,
+                        self.encoding.name()
+                    ),
+                    UnmappableMode::Replace => out.push(b'?'),
+                    UnmappableMode::Skip => {}
+                },
+            }
+        }
+    }
+}
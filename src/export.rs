@@ -1,11 +1,23 @@
-use crate::config::{CompressionType, ExportConfig, ExportFormat};
+use crate::config::{CompressionType, ExportConfig, ExportFormat, SchemaOutput};
 use crate::db::oracle::OracleDatabase;
+use crate::db::ColumnInfo;
+use crate::encoding;
+use crate::fixed;
+use crate::format::ColumnFormatter;
+use crate::lob::LobWriter;
+use crate::manifest;
+use crate::mapping::ColumnMapper;
+use crate::masking::Masker;
+use crate::schema;
+use crate::sink::{self, Sink, Target};
+use crate::sqlldr;
+use crate::transform::RowTransformer;
+use crate::writer;
 use anyhow::{Context, Result};
-use csv::WriterBuilder;
 use flate2::write::GzEncoder;
 use flate2::Compression;
-use std::fs::File;
-use std::io::{BufWriter, Write};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
@@ -25,56 +37,100 @@ impl Exporter {
         let row_count = Arc::new(AtomicU64::new(0));
         let mut io_write_time = 0.0;
 
-        let file = File::create(&self.config.output_file)
-            .context("Failed to create output file")?;
+        // 输出可能是标准输出、命名管道或远程存储，文件大小按写入的字节数统计
+        let bytes_written = Arc::new(AtomicU64::new(0));
+        let file = CountingWriter {
+            inner: sink::open(&self.config)?,
+            count: Arc::clone(&bytes_written),
+        };
         
-        let writer: Box<dyn Write> = match self.config.compression {
-            CompressionType::Gzip => {
-                Box::new(BufWriter::with_capacity(
-                    self.config.buffer_size,
-                    GzEncoder::new(file, Compression::default())
-                ))
-            }
-            CompressionType::None => {
-                Box::new(BufWriter::with_capacity(self.config.buffer_size, file))
-            }
+        let mut writer = match self.config.compression {
+            CompressionType::Gzip => OutputWriter::Gzip(BufWriter::with_capacity(
+                self.config.buffer_size,
+                GzEncoder::new(file, Compression::default()),
+            )),
+            CompressionType::None => OutputWriter::Plain(BufWriter::with_capacity(self.config.buffer_size, file)),
         };
-        let mut writer = writer;
 
-        let delimiter = self.get_delimiter();
+        if self.config.bom {
+            writer.write_all(encoding::UTF8_BOM)?;
+        }
         
-        // 先获取列信息
+        // 先获取列信息，追加计算列后按映射确定输出列
         let columns = db.get_column_info(&self.config.query)?;
+        let source_count = columns.len();
+        let transformer = RowTransformer::new(&self.config, &columns)?;
+        let columns = transformer.columns(&columns);
+        let mapper = ColumnMapper::new(&self.config.mapping, &columns)?;
+        let mut lob_writer = LobWriter::new(&self.config, &mapper.source_columns(&columns));
+        let masker = Masker::new(&self.config.masking, &mapper.source_columns(&columns))?;
+        let formatter = ColumnFormatter::new(&self.config.columns, &mapper.source_columns(&columns))?;
+        let control_file = if self.config.sqlldr {
+            Some(sqlldr::write_control_file(&self.config, &mapper.output_columns(&columns), &formatter, &masker)?)
+        } else {
+            None
+        };
         
-        // 如果需要表头，先写入
-        if self.config.include_header {
-            self.write_row(&mut *writer, &columns, delimiter)?;
-        }
+        // 写入表头或脚本开头
+        let value_columns = writer::value_columns(&self.config, &mapper.output_columns(&columns), &masker);
+        let schema_file = schema::write_schema_file(db, &self.config, &mapper.output_columns(&columns), &value_columns)?;
+        let layout_file = if matches!(self.config.format, ExportFormat::FixedWidth) {
+            Some(fixed::write_layout(&self.config, &value_columns, &formatter)?)
+        } else {
+            None
+        };
+        let mut row_writer = writer::create(&self.config, &value_columns, formatter, db.fetch_size())?;
+        row_writer.begin(&mut writer, mapper.headers())?;
         
         // 流式写入数据
         let row_count_clone = Arc::clone(&row_count);
         let show_progress = self.config.show_progress;
         let progress_interval = self.config.progress_interval;
         
+        let mut rows_fetched = 0u64;
+        let mut rows_filtered = 0u64;
+
+        let spool = lob_writer.blob_spool(mapper.indices(), source_count, &masker)?;
+
         let db_start = Instant::now();
-        db.execute_query_streaming(&self.config.query, |row_values| {
+        db.execute_query_streaming(&self.config.query, &self.config.lob, spool.as_ref(), |values| {
+            rows_fetched += 1;
+            let spooled = LobWriter::spooled_files(&values);
+            let Some(values) = transformer
+                .apply(values)
+                .with_context(|| format!("Failed to process fetched row {}", rows_fetched))?
+            else {
+                LobWriter::discard(spooled);
+                rows_filtered += 1;
+                return Ok(());
+            };
             let count = row_count_clone.fetch_add(1, Ordering::Relaxed) + 1;
             
             // 使用日志输出进度信息
+            #[allow(clippy::manual_is_multiple_of)]
             if show_progress && count % progress_interval == 0 {
                 let elapsed = db_start.elapsed().as_secs_f64();
                 let speed = count as f64 / elapsed;
                 info!("Progress: {} rows exported ({:.2} rows/sec)", count, speed);
             }
             
+            // 先脱敏再处理LOB，脱敏后的二进制列不再是原始字节，不会写入附属文件
+            let values = masker.apply(mapper.apply(values));
+            let values = lob_writer
+                .apply(count, values)
+                .with_context(|| format!("Failed to write LOB data for row {}", count))?;
             let io_start = Instant::now();
-            self.write_row(&mut *writer, &row_values, delimiter)?;
+            row_writer
+                .write_row(&mut writer, values)
+                .with_context(|| format!("Failed to write row {}", count))?;
             io_write_time += io_start.elapsed().as_secs_f64();
             Ok(())
         })?;
         let db_read_time = db_start.elapsed().as_secs_f64();
         
-        writer.flush()?;
+        row_writer.finish(&mut writer)?;
+        // 写入gzip结尾并提交到输出目标（如完成分段上传），之后再统计大小
+        writer.finish().context("Failed to finish output file")?;
 
         let rows = row_count.load(Ordering::Relaxed);
         if show_progress {
@@ -82,8 +138,19 @@ impl Exporter {
         }
 
         let duration = start_time.elapsed();
-        let file_size = std::fs::metadata(&self.config.output_file)?.len();
+        let file_size = bytes_written.load(Ordering::Relaxed);
         let rows = row_count.load(Ordering::Relaxed);
+        let snapshot_scn = db.snapshot_scn();
+        let manifest_file = if manifest::enabled(&self.config) {
+            Some(manifest::write_manifest(
+                &self.config,
+                &mapper.output_columns(&columns),
+                rows,
+                snapshot_scn,
+            )?)
+        } else {
+            None
+        };
         let avg_row_size = if rows > 0 {
             file_size as f64 / rows as f64
         } else {
@@ -92,57 +159,133 @@ impl Exporter {
 
         Ok(ExportStats {
             rows_exported: rows,
+            rows_filtered: transformer.has_filter().then_some(rows_filtered),
+            lob_files: lob_writer.files_written(),
             duration_secs: duration.as_secs_f64(),
             file_size_bytes: file_size,
             db_read_time_secs: db_read_time,
             io_write_time_secs: io_write_time,
             avg_row_size_bytes: avg_row_size,
             output_file: self.config.output_file.clone(),
+            snapshot_scn,
+            schema_file,
+            manifest_file,
+            control_file,
+            layout_file,
         })
     }
 
-    fn get_delimiter(&self) -> u8 {
-        match self.config.format {
-            ExportFormat::Csv => {
-                if self.config.delimiter.len() == 1 {
-                    self.config.delimiter.as_bytes()[0]
-                } else {
-                    b','
-                }
-            },
-            ExportFormat::Tsv => b'\t',
-            ExportFormat::Custom => {
-                if self.config.delimiter.len() == 1 {
-                    self.config.delimiter.as_bytes()[0]
-                } else {
-                    b','
-                }
-            }
+    /// 试运行：解析查询并获取执行计划，不读取数据也不写入文件
+    pub fn dry_run(&self, db: &mut OracleDatabase) -> Result<DryRunReport> {
+        let columns = db.describe_query(&self.config.query)?;
+        let columns = RowTransformer::new(&self.config, &columns)?.columns(&columns);
+        let mapper = ColumnMapper::new(&self.config.mapping, &columns)?;
+        let masker = Masker::new(&self.config.masking, &mapper.source_columns(&columns))?;
+        let formatter = ColumnFormatter::new(&self.config.columns, &mapper.source_columns(&columns))?;
+        let columns = mapper.output_columns(&columns);
+        let control_file = if self.config.sqlldr {
+            sqlldr::render(&self.config, &columns, &formatter, &masker)?;
+            Some(sqlldr::control_path(&self.config))
+        } else {
+            None
+        };
+        let layout_file = if matches!(self.config.format, ExportFormat::FixedWidth) {
+            fixed::Layout::new(&self.config, &writer::value_columns(&self.config, &columns, &masker), &formatter)?;
+            Some(fixed::layout_path(&self.config))
+        } else {
+            None
+        };
+        let (plan, estimated_rows) = db.explain_plan(&self.config.query)?;
+
+        let schema_file = match self.config.schema_output {
+            SchemaOutput::None => None,
+            SchemaOutput::Json => Some(schema::sidecar_path(&self.config.output_file, "schema.json")),
+            SchemaOutput::Ddl => Some(schema::sidecar_path(&self.config.output_file, "ddl.sql")),
+        };
+        let manifest_file = manifest::enabled(&self.config).then(|| manifest::manifest_path(&self.config));
+
+        Ok(DryRunReport {
+            output_file: self.config.output_file.clone(),
+            output_exists: matches!(Target::parse(&self.config.output_file)?, Target::File(path) if Path::new(&path).exists()),
+            columns,
+            plan,
+            estimated_rows,
+            schema_file,
+            manifest_file,
+            control_file,
+            layout_file,
+        })
+    }
+}
+
+/// 统计写入的字节数
+struct CountingWriter {
+    inner: Box<dyn Sink>,
+    count: Arc<AtomicU64>,
+}
+
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.count.fetch_add(written as u64, Ordering::Relaxed);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// 带缓冲（及压缩）的输出
+enum OutputWriter {
+    Plain(BufWriter<CountingWriter>),
+    Gzip(BufWriter<GzEncoder<CountingWriter>>),
+}
+
+impl OutputWriter {
+    /// 刷新缓冲区、写入压缩结尾并提交输出目标
+    fn finish(self) -> Result<()> {
+        let counting = match self {
+            OutputWriter::Plain(writer) => writer.into_inner().map_err(|e| e.into_error())?,
+            OutputWriter::Gzip(writer) => writer.into_inner().map_err(|e| e.into_error())?.finish()?,
+        };
+        counting.inner.finish()
+    }
+}
+
+impl Write for OutputWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            OutputWriter::Plain(writer) => writer.write(buf),
+            OutputWriter::Gzip(writer) => writer.write(buf),
         }
     }
 
-    fn write_row(&self, writer: &mut dyn Write, values: &[String], delimiter: u8) -> Result<()> {
-        let buffer = Vec::with_capacity(1024);
-        let mut csv_writer = WriterBuilder::new()
-            .delimiter(delimiter)
-            .from_writer(buffer);
-        
-        csv_writer.write_record(values)?;
-        let data = csv_writer.into_inner()?;
-        writer.write_all(&data)?;
-        
-        Ok(())
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            OutputWriter::Plain(writer) => writer.flush(),
+            OutputWriter::Gzip(writer) => writer.flush(),
+        }
     }
 }
 
 pub struct ExportStats {
     pub rows_exported: u64,
+    /// 被过滤条件排除的行数（未配置过滤条件时为None）
+    pub rows_filtered: Option<u64>,
+    /// 写入的LOB附属文件数
+    pub lob_files: u64,
     pub duration_secs: f64,
     pub file_size_bytes: u64,
     pub db_read_time_secs: f64,
     pub io_write_time_secs: f64,
     pub avg_row_size_bytes: f64,
     pub output_file: String,
+    pub snapshot_scn: Option<u64>,
+    pub schema_file: Option<String>,
+    pub manifest_file: Option<String>,
+    pub control_file: Option<String>,
+    pub layout_file: Option<String>,
 }
 
 impl ExportStats {
@@ -150,6 +293,27 @@ impl ExportStats {
         info!("Export Summary:");
         info!("  Output file: {}", self.output_file);
         info!("  Rows exported: {}", self.rows_exported);
+        if let Some(filtered) = self.rows_filtered {
+            info!("  Rows filtered: {}", filtered);
+        }
+        if let Some(scn) = self.snapshot_scn {
+            info!("  Snapshot SCN: {}", scn);
+        }
+        if let Some(ref path) = self.schema_file {
+            info!("  Schema file: {}", path);
+        }
+        if let Some(ref path) = self.manifest_file {
+            info!("  Manifest file: {}", path);
+        }
+        if let Some(ref path) = self.control_file {
+            info!("  Control file: {}", path);
+        }
+        if let Some(ref path) = self.layout_file {
+            info!("  Layout file: {}", path);
+        }
+        if self.lob_files > 0 {
+            info!("  LOB files: {}", self.lob_files);
+        }
         info!("  Duration: {:.2} seconds", self.duration_secs);
         info!("  File size: {} bytes ({:.2} MB)", 
             self.file_size_bytes, 
@@ -178,3 +342,58 @@ impl ExportStats {
         }
     }
 }
+
+/// 试运行结果
+pub struct DryRunReport {
+    pub output_file: String,
+    pub output_exists: bool,
+    pub columns: Vec<ColumnInfo>,
+    pub plan: Vec<String>,
+    pub estimated_rows: Option<u64>,
+    pub schema_file: Option<String>,
+    pub manifest_file: Option<String>,
+    pub control_file: Option<String>,
+    pub layout_file: Option<String>,
+}
+
+impl DryRunReport {
+    pub fn print_summary(&self) {
+        info!("Dry Run Summary:");
+        if self.output_exists {
+            info!("  Output file: {} (exists, would be overwritten)", self.output_file);
+        } else {
+            info!("  Output file: {}", self.output_file);
+        }
+        if let Some(ref path) = self.schema_file {
+            info!("  Schema file: {}", path);
+        }
+        if let Some(ref path) = self.manifest_file {
+            info!("  Manifest file: {}", path);
+        }
+        if let Some(ref path) = self.control_file {
+            info!("  Control file: {}", path);
+        }
+        if let Some(ref path) = self.layout_file {
+            info!("  Layout file: {}", path);
+        }
+        match self.estimated_rows {
+            Some(rows) => info!("  Estimated rows: {}", rows),
+            None => info!("  Estimated rows: unknown"),
+        }
+
+        info!("Columns ({}):", self.columns.len());
+        for column in &self.columns {
+            info!(
+                "  {} {}{}",
+                column.name,
+                column.type_name,
+                if column.nullable { "" } else { " NOT NULL" }
+            );
+        }
+
+        info!("Execution Plan:");
+        for line in &self.plan {
+            info!("  {}", line);
+        }
+    }
+}
@@ -0,0 +1,1197 @@
+use crate::db::{ColumnInfo, ColumnKind, Value};
+use anyhow::{anyhow, bail, Result};
+use chrono::NaiveDateTime;
+use std::cmp::Ordering;
+
+/// 行过滤及计算列使用的表达式，语法接近SQL：
+/// `AMOUNT > 0 AND STATUS != 'X'`、`concat(FIRST_NAME, ' ', LAST_NAME)`
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Column { name: String, index: usize },
+    Literal(Value),
+    Neg(Box<Expr>),
+    Not(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    IsNull { expr: Box<Expr>, negated: bool },
+    In { expr: Box<Expr>, list: Vec<Expr>, negated: bool },
+    Like { expr: Box<Expr>, pattern: Box<Expr>, negated: bool },
+    Call(Function, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    And,
+    Or,
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Concat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    Concat,
+    Upper,
+    Lower,
+    Trim,
+    Ltrim,
+    Rtrim,
+    Substr,
+    Length,
+    Replace,
+    Coalesce,
+    Round,
+    Abs,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        let function = match name.to_ascii_lowercase().as_str() {
+            "concat" => Function::Concat,
+            "upper" => Function::Upper,
+            "lower" => Function::Lower,
+            "trim" => Function::Trim,
+            "ltrim" => Function::Ltrim,
+            "rtrim" => Function::Rtrim,
+            "substr" => Function::Substr,
+            "length" => Function::Length,
+            "replace" => Function::Replace,
+            "coalesce" | "nvl" => Function::Coalesce,
+            "round" => Function::Round,
+            "abs" => Function::Abs,
+            _ => return None,
+        };
+        Some(function)
+    }
+
+    /// 参数个数范围
+    fn arity(self) -> (usize, usize) {
+        match self {
+            Function::Concat | Function::Coalesce => (1, usize::MAX),
+            Function::Upper
+            | Function::Lower
+            | Function::Trim
+            | Function::Ltrim
+            | Function::Rtrim
+            | Function::Length
+            | Function::Abs => (1, 1),
+            Function::Substr | Function::Replace => (2, 3),
+            Function::Round => (1, 2),
+        }
+    }
+}
+
+impl Expr {
+    /// 解析表达式文本，列名在 `bind` 时解析
+    pub fn parse(text: &str) -> Result<Expr> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            bail!("unexpected {} at position {}", token.kind, token.offset + 1);
+        }
+        Ok(expr)
+    }
+
+    /// 将列名解析为查询结果中的位置（不区分大小写），返回表达式的结果类型
+    pub fn bind(&mut self, columns: &[ColumnInfo]) -> Result<ColumnKind> {
+        let kind = match self {
+            Expr::Column { name, index } => {
+                let Some(position) = columns.iter().position(|c| c.name.eq_ignore_ascii_case(name)) else {
+                    bail!(
+                        "unknown column {} (columns: {})",
+                        name,
+                        columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>().join(", ")
+                    );
+                };
+                *index = position;
+                columns[position].kind
+            }
+            Expr::Literal(value) => match value {
+                Value::Number(_) => ColumnKind::Number,
+                Value::Boolean(_) => ColumnKind::Boolean,
+                _ => ColumnKind::Text,
+            },
+            Expr::Neg(expr) => {
+                expr.bind(columns)?;
+                ColumnKind::Number
+            }
+            Expr::Not(expr) => {
+                expr.bind(columns)?;
+                ColumnKind::Boolean
+            }
+            Expr::Binary(op, left, right) => {
+                left.bind(columns)?;
+                right.bind(columns)?;
+                match op {
+                    BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div => ColumnKind::Number,
+                    BinaryOp::Concat => ColumnKind::Text,
+                    _ => ColumnKind::Boolean,
+                }
+            }
+            Expr::IsNull { expr, .. } => {
+                expr.bind(columns)?;
+                ColumnKind::Boolean
+            }
+            Expr::In { expr, list, .. } => {
+                expr.bind(columns)?;
+                for item in list {
+                    item.bind(columns)?;
+                }
+                ColumnKind::Boolean
+            }
+            Expr::Like { expr, pattern, .. } => {
+                expr.bind(columns)?;
+                pattern.bind(columns)?;
+                ColumnKind::Boolean
+            }
+            Expr::Call(function, args) => {
+                let mut kinds = Vec::with_capacity(args.len());
+                for arg in args.iter_mut() {
+                    kinds.push(arg.bind(columns)?);
+                }
+                match function {
+                    Function::Length | Function::Round | Function::Abs => ColumnKind::Number,
+                    // 取第一个非NULL字面量参数的类型
+                    Function::Coalesce => args
+                        .iter()
+                        .zip(&kinds)
+                        .find(|(arg, _)| !matches!(arg, Expr::Literal(Value::Null)))
+                        .map(|(_, &kind)| kind)
+                        .unwrap_or(ColumnKind::Text),
+                    _ => ColumnKind::Text,
+                }
+            }
+        };
+        Ok(kind)
+    }
+
+    /// 按行求值，`row` 为 `bind` 时的列顺序
+    pub fn eval(&self, row: &[Value]) -> Result<Value> {
+        let value = match self {
+            Expr::Column { index, .. } => row[*index].clone(),
+            Expr::Literal(value) => value.clone(),
+            Expr::Neg(expr) => match to_number(&expr.eval(row)?)? {
+                Some(n) => n.neg()?.into_value(),
+                None => Value::Null,
+            },
+            Expr::Not(expr) => match truth(&expr.eval(row)?) {
+                Some(b) => Value::Boolean(!b),
+                None => Value::Null,
+            },
+            Expr::Binary(BinaryOp::And, left, right) => {
+                // 三值逻辑：FALSE AND NULL 为 FALSE
+                let left = truth(&left.eval(row)?);
+                if left == Some(false) {
+                    return Ok(Value::Boolean(false));
+                }
+                match (left, truth(&right.eval(row)?)) {
+                    (_, Some(false)) => Value::Boolean(false),
+                    (Some(true), Some(true)) => Value::Boolean(true),
+                    _ => Value::Null,
+                }
+            }
+            Expr::Binary(BinaryOp::Or, left, right) => {
+                let left = truth(&left.eval(row)?);
+                if left == Some(true) {
+                    return Ok(Value::Boolean(true));
+                }
+                match (left, truth(&right.eval(row)?)) {
+                    (_, Some(true)) => Value::Boolean(true),
+                    (Some(false), Some(false)) => Value::Boolean(false),
+                    _ => Value::Null,
+                }
+            }
+            Expr::Binary(BinaryOp::Concat, left, right) => {
+                // 与Oracle一致，NULL按空字符串拼接
+                let mut text = left.eval(row)?.into_string();
+                text.push_str(&right.eval(row)?.into_string());
+                Value::Text(text)
+            }
+            Expr::Binary(op, left, right) => {
+                let left = left.eval(row)?;
+                let right = right.eval(row)?;
+                match op {
+                    BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div => {
+                        let (Some(a), Some(b)) = (to_number(&left)?, to_number(&right)?) else {
+                            return Ok(Value::Null);
+                        };
+                        match op {
+                            BinaryOp::Add => a.add(&b)?,
+                            BinaryOp::Sub => a.add(&b.neg()?)?,
+                            BinaryOp::Mul => a.mul(&b)?,
+                            _ => a.div(&b)?,
+                        }
+                        .into_value()
+                    }
+                    _ => match compare(&left, &right) {
+                        Some(ordering) => Value::Boolean(match op {
+                            BinaryOp::Eq => ordering == Ordering::Equal,
+                            BinaryOp::NotEq => ordering != Ordering::Equal,
+                            BinaryOp::Lt => ordering == Ordering::Less,
+                            BinaryOp::LtEq => ordering != Ordering::Greater,
+                            BinaryOp::Gt => ordering == Ordering::Greater,
+                            _ => ordering != Ordering::Less,
+                        }),
+                        None => Value::Null,
+                    },
+                }
+            }
+            Expr::IsNull { expr, negated } => Value::Boolean((expr.eval(row)? == Value::Null) != *negated),
+            Expr::In { expr, list, negated } => {
+                let value = expr.eval(row)?;
+                if value == Value::Null {
+                    return Ok(Value::Null);
+                }
+                let mut unknown = false;
+                for item in list {
+                    match compare(&value, &item.eval(row)?) {
+                        Some(Ordering::Equal) => return Ok(Value::Boolean(!negated)),
+                        Some(_) => {}
+                        None => unknown = true,
+                    }
+                }
+                if unknown { Value::Null } else { Value::Boolean(*negated) }
+            }
+            Expr::Like { expr, pattern, negated } => {
+                let value = expr.eval(row)?;
+                let pattern = pattern.eval(row)?;
+                if value == Value::Null || pattern == Value::Null {
+                    return Ok(Value::Null);
+                }
+                let text: Vec<char> = value.to_string().chars().collect();
+                let pattern: Vec<char> = pattern.to_string().chars().collect();
+                Value::Boolean(like(&text, &pattern) != *negated)
+            }
+            Expr::Call(function, args) => call(*function, args, row)?,
+        };
+        Ok(value)
+    }
+}
+
+/// 条件的真值，NULL为未知
+pub fn truth(value: &Value) -> Option<bool> {
+    match value {
+        Value::Null => None,
+        Value::Boolean(b) => Some(*b),
+        Value::Number(s) => Some(s.parse::<f64>().map(|n| n != 0.0).unwrap_or(true)),
+        other => Some(!other.to_string().is_empty()),
+    }
+}
+
+fn call(function: Function, args: &[Expr], row: &[Value]) -> Result<Value> {
+    let value = match function {
+        Function::Concat => {
+            let mut text = String::new();
+            for arg in args {
+                text.push_str(&arg.eval(row)?.into_string());
+            }
+            Value::Text(text)
+        }
+        Function::Coalesce => {
+            for arg in args {
+                let value = arg.eval(row)?;
+                if value != Value::Null {
+                    return Ok(value);
+                }
+            }
+            Value::Null
+        }
+        Function::Upper | Function::Lower | Function::Trim | Function::Ltrim | Function::Rtrim | Function::Length => {
+            let value = args[0].eval(row)?;
+            if value == Value::Null {
+                return Ok(Value::Null);
+            }
+            let text = value.into_string();
+            match function {
+                Function::Upper => Value::Text(text.to_uppercase()),
+                Function::Lower => Value::Text(text.to_lowercase()),
+                Function::Trim => Value::Text(text.trim().to_string()),
+                Function::Ltrim => Value::Text(text.trim_start().to_string()),
+                Function::Rtrim => Value::Text(text.trim_end().to_string()),
+                _ => Value::Number(text.chars().count().to_string()),
+            }
+        }
+        Function::Substr => {
+            let value = args[0].eval(row)?;
+            let Some(start) = to_integer(&args[1].eval(row)?)? else {
+                return Ok(Value::Null);
+            };
+            let length = match args.get(2) {
+                Some(arg) => match to_integer(&arg.eval(row)?)? {
+                    Some(n) => Some(n.max(0) as usize),
+                    None => return Ok(Value::Null),
+                },
+                None => None,
+            };
+            if value == Value::Null {
+                return Ok(Value::Null);
+            }
+            // 与Oracle一致：位置从1开始，0视为1，负数从末尾计算
+            let chars: Vec<char> = value.into_string().chars().collect();
+            let begin = match start {
+                s if s > 0 => (s - 1) as usize,
+                0 => 0,
+                s => match chars.len().checked_sub(s.unsigned_abs() as usize) {
+                    Some(begin) => begin,
+                    None => return Ok(Value::Null),
+                },
+            };
+            let end = match length {
+                Some(length) => begin.saturating_add(length).min(chars.len()),
+                None => chars.len(),
+            };
+            if begin >= end {
+                return Ok(Value::Null);
+            }
+            Value::Text(chars[begin..end].iter().collect())
+        }
+        Function::Replace => {
+            let value = args[0].eval(row)?;
+            if value == Value::Null {
+                return Ok(Value::Null);
+            }
+            let text = value.into_string();
+            let search = args[1].eval(row)?.into_string();
+            let replacement = match args.get(2) {
+                Some(arg) => arg.eval(row)?.into_string(),
+                None => String::new(),
+            };
+            if search.is_empty() {
+                Value::Text(text)
+            } else {
+                Value::Text(text.replace(&search, &replacement))
+            }
+        }
+        Function::Round => {
+            let Some(n) = to_number(&args[0].eval(row)?)? else {
+                return Ok(Value::Null);
+            };
+            let scale = match args.get(1) {
+                Some(arg) => match to_integer(&arg.eval(row)?)? {
+                    Some(scale) => scale,
+                    None => return Ok(Value::Null),
+                },
+                None => 0,
+            };
+            n.round(scale)?.into_value()
+        }
+        Function::Abs => match to_number(&args[0].eval(row)?)? {
+            Some(n) if n.value < 0 => n.neg()?.into_value(),
+            Some(n) => n.into_value(),
+            None => Value::Null,
+        },
+    };
+    Ok(value)
+}
+
+/// 转换为数字，NULL返回None；无法解析的文本报错
+fn to_number(value: &Value) -> Result<Option<Decimal>> {
+    match value {
+        Value::Null => Ok(None),
+        Value::Number(s) | Value::Text(s) => Decimal::parse(s.trim()).map(Some),
+        Value::Boolean(b) => Ok(Some(Decimal::from_int(*b as i128))),
+        other => bail!("cannot convert {:?} to a number", other.to_string()),
+    }
+}
+
+/// 转换为整数（截去小数部分），用于位置、长度等参数
+fn to_integer(value: &Value) -> Result<Option<i64>> {
+    let Some(n) = to_number(value)? else {
+        return Ok(None);
+    };
+    let integer = n.value / pow10(n.scale)?;
+    i64::try_from(integer)
+        .map(Some)
+        .map_err(|_| anyhow!("{} is out of range for an integer argument", n))
+}
+
+/// 表达式中参与运算的数字：`value / 10^scale`。
+/// 按十进制计算，与Oracle的NUMBER一致最多38位有效数字：中间结果按256位整数精确计算，
+/// 超出时舍入小数部分，整数部分超出时报错
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Decimal {
+    value: i128,
+    scale: u32,
+}
+
+/// 小数位数上限（10^38仍在i128范围内）
+const MAX_SCALE: u32 = 38;
+/// 除不尽时保留的小数位数
+const DIV_SCALE: u32 = 20;
+
+impl Decimal {
+    fn from_int(value: i128) -> Self {
+        Self { value, scale: 0 }
+    }
+
+    /// 解析十进制文本，支持科学计数法（如BINARY_DOUBLE列的 `1.5E+20`）
+    fn parse(text: &str) -> Result<Self> {
+        let invalid = || anyhow!("cannot convert {:?} to a number", text);
+        let out_of_range = || anyhow!("{} exceeds the 38-digit precision of numeric expressions", text);
+        let (mantissa, exponent) = match text.find(['e', 'E']) {
+            Some(pos) => (&text[..pos], text[pos + 1..].parse::<i32>().map_err(|_| invalid())?),
+            None => (text, 0),
+        };
+        let (negative, digits) = match mantissa.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, mantissa.strip_prefix('+').unwrap_or(mantissa)),
+        };
+        let (int_part, frac_part) = digits.split_once('.').unwrap_or((digits, ""));
+        if int_part.is_empty() && frac_part.is_empty()
+            || !int_part.chars().chain(frac_part.chars()).all(|c| c.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+
+        let mut value: i128 = 0;
+        for c in int_part.chars().chain(frac_part.trim_end_matches('0').chars()) {
+            value = value
+                .checked_mul(10)
+                .and_then(|v| v.checked_add(c.to_digit(10).unwrap_or(0) as i128))
+                .ok_or_else(out_of_range)?;
+        }
+        let scale = frac_part.trim_end_matches('0').len() as i64 - exponent as i64;
+        let decimal = if scale < 0 {
+            let value = u32::try_from(-scale)
+                .ok()
+                .and_then(|e| value.checked_mul(pow10(e).ok()?))
+                .ok_or_else(out_of_range)?;
+            Self::from_int(value)
+        } else {
+            let scale = u32::try_from(scale).ok().filter(|s| *s <= MAX_SCALE).ok_or_else(out_of_range)?;
+            Self { value, scale }
+        };
+        Ok(if negative { Self { value: -decimal.value, ..decimal } } else { decimal }.normalize())
+    }
+
+    /// 去掉末尾的0
+    fn normalize(mut self) -> Self {
+        while self.scale > 0 && self.value % 10 == 0 {
+            self.value /= 10;
+            self.scale -= 1;
+        }
+        self
+    }
+
+    /// 调整到更大的小数位数
+    fn rescale(&self, scale: u32) -> Option<i128> {
+        self.value.checked_mul(pow10(scale - self.scale).ok()?)
+    }
+
+    fn neg(&self) -> Result<Self> {
+        let value = self.value.checked_neg().ok_or_else(overflow)?;
+        Ok(Self { value, ..*self })
+    }
+
+    fn add(&self, other: &Self) -> Result<Self> {
+        let scale = self.scale.max(other.scale);
+        let a = Wide::from(self.value.unsigned_abs()).mul_pow10(scale - self.scale)?;
+        let b = Wide::from(other.value.unsigned_abs()).mul_pow10(scale - other.scale)?;
+        let (a_negative, b_negative) = (self.value < 0, other.value < 0);
+        let (negative, magnitude) = if a_negative == b_negative {
+            (a_negative, a.add(b).ok_or_else(overflow)?)
+        } else if a >= b {
+            (a_negative, a.sub(b))
+        } else {
+            (b_negative, b.sub(a))
+        };
+        Self::from_wide(negative, magnitude, scale, MAX_SCALE)
+    }
+
+    fn mul(&self, other: &Self) -> Result<Self> {
+        let magnitude = Wide::mul(self.value.unsigned_abs(), other.value.unsigned_abs());
+        let negative = (self.value < 0) != (other.value < 0);
+        Self::from_wide(negative, magnitude, self.scale + other.scale, MAX_SCALE)
+    }
+
+    /// 除法，除不尽时保留 `DIV_SCALE` 位小数（操作数的小数位更多时与其一致）并四舍五入
+    fn div(&self, other: &Self) -> Result<Self> {
+        if other.value == 0 {
+            bail!("division by zero");
+        }
+        let dividend = self.value.unsigned_abs();
+        let divisor = other.value.unsigned_abs();
+        let target = DIV_SCALE.max(self.scale).max(other.scale);
+        // 已经足够舍入到i128的商
+        let limit = Wide::from(1).mul_pow10(MAX_SCALE + 2)?;
+
+        // 逐位长除：quotient = dividend * 10^k / divisor，结果的小数位数为 k + self.scale - other.scale，
+        // 多算一位用于四舍五入
+        let mut quotient = Wide::from(dividend / divisor);
+        let mut remainder = dividend % divisor;
+        let mut scale = self.scale as i64 - other.scale as i64;
+        while scale < 0 || (scale <= target as i64 && quotient < limit) {
+            let mut rest = Wide::from(remainder).mul_small(10).ok_or_else(overflow)?;
+            let mut digit = 0;
+            while rest >= Wide::from(divisor) {
+                rest = rest.sub(Wide::from(divisor));
+                digit += 1;
+            }
+            remainder = rest.lo;
+            quotient = quotient
+                .mul_small(10)
+                .and_then(|q| q.add(Wide::from(digit)))
+                .ok_or_else(overflow)?;
+            scale += 1;
+        }
+        let negative = (self.value < 0) != (other.value < 0);
+        Self::from_wide(negative, quotient, scale as u32, target)
+    }
+
+    /// 将精确的中间结果舍入到最多 `max_scale` 位小数并能以i128表示
+    fn from_wide(negative: bool, mut magnitude: Wide, mut scale: u32, max_scale: u32) -> Result<Self> {
+        let max = Wide::from(i128::MAX as u128);
+        loop {
+            let mut dropped = 0;
+            while scale > max_scale || (scale > 0 && magnitude > max) {
+                let (quotient, digit) = magnitude.div_small(10);
+                magnitude = quotient;
+                dropped = digit;
+                scale -= 1;
+            }
+            if dropped >= 5 {
+                magnitude = magnitude.add(Wide::from(1)).ok_or_else(overflow)?;
+            }
+            // 进位后可能再次超出i128
+            if magnitude <= max || scale == 0 {
+                break;
+            }
+        }
+        if magnitude > max {
+            return Err(overflow());
+        }
+        let value = magnitude.lo as i128;
+        Ok(Self { value: if negative { -value } else { value }, scale }.normalize())
+    }
+
+    /// 四舍五入到 `scale` 位小数，负数表示舍入到整数位
+    fn round(&self, scale: i64) -> Result<Self> {
+        let scale = scale.clamp(-(MAX_SCALE as i64), MAX_SCALE as i64);
+        if scale >= self.scale as i64 {
+            return Ok(*self);
+        }
+        let digits = (self.scale as i64 - scale) as u32;
+        let rounded = match pow10(digits) {
+            Ok(factor) => round_div(self.value, factor),
+            // 舍去的位数超过38位时结果为0
+            Err(_) => 0,
+        };
+        if scale >= 0 {
+            return Ok(Self { value: rounded, scale: scale as u32 }.normalize());
+        }
+        let value = rounded.checked_mul(pow10(-scale as u32)?).ok_or_else(overflow)?;
+        Ok(Self::from_int(value))
+    }
+
+    fn into_value(self) -> Value {
+        Value::Number(self.to_string())
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let scale = self.scale.max(other.scale);
+        match (self.rescale(scale), other.rescale(scale)) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            // 无法对齐小数位时先比较整数部分（小数位数不超过38，对齐只可能在整数部分很大时溢出）
+            _ => {
+                let (a, b) = (pow10(self.scale).unwrap_or(1), pow10(other.scale).unwrap_or(1));
+                (self.value / a).cmp(&(other.value / b)).then_with(|| {
+                    let (fa, fb) = (self.value % a, other.value % b);
+                    (fa * pow10(scale - self.scale).unwrap_or(1)).cmp(&(fb * pow10(scale - other.scale).unwrap_or(1)))
+                })
+            }
+        }
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Display for Decimal {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let digits = self.value.unsigned_abs().to_string();
+        let sign = if self.value < 0 { "-" } else { "" };
+        let scale = self.scale as usize;
+        if scale == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (int_part, frac_part) = digits.split_at(digits.len() - scale);
+        write!(f, "{}{}.{}", sign, int_part, frac_part)
+    }
+}
+
+fn pow10(exponent: u32) -> Result<i128> {
+    10i128.checked_pow(exponent).ok_or_else(overflow)
+}
+
+/// 整数除法，四舍五入（远离0）
+fn round_div(dividend: i128, divisor: i128) -> i128 {
+    let quotient = dividend / divisor;
+    let remainder = (dividend % divisor).unsigned_abs();
+    if remainder >= divisor.unsigned_abs() - remainder {
+        if (dividend < 0) != (divisor < 0) { quotient - 1 } else { quotient + 1 }
+    } else {
+        quotient
+    }
+}
+
+/// 256位无符号整数，只实现十进制运算需要的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Wide {
+    hi: u128,
+    lo: u128,
+}
+
+impl Wide {
+    fn from(value: u128) -> Self {
+        Self { hi: 0, lo: value }
+    }
+
+    fn mul(a: u128, b: u128) -> Self {
+        const MASK: u128 = u64::MAX as u128;
+        let (a1, a0) = (a >> 64, a & MASK);
+        let (b1, b0) = (b >> 64, b & MASK);
+        let (low, cross1, cross2, high) = (a0 * b0, a0 * b1, a1 * b0, a1 * b1);
+        let (lo, carry1) = low.overflowing_add(cross1 << 64);
+        let (lo, carry2) = lo.overflowing_add(cross2 << 64);
+        let hi = high + (cross1 >> 64) + (cross2 >> 64) + carry1 as u128 + carry2 as u128;
+        Self { hi, lo }
+    }
+
+    fn mul_small(self, factor: u128) -> Option<Self> {
+        let low = Self::mul(self.lo, factor);
+        let hi = self.hi.checked_mul(factor)?.checked_add(low.hi)?;
+        Some(Self { hi, lo: low.lo })
+    }
+
+    fn mul_pow10(self, exponent: u32) -> Result<Self> {
+        (0..exponent).try_fold(self, |value, _| value.mul_small(10).ok_or_else(overflow))
+    }
+
+    fn add(self, other: Self) -> Option<Self> {
+        let (lo, carry) = self.lo.overflowing_add(other.lo);
+        let hi = self.hi.checked_add(other.hi)?.checked_add(carry as u128)?;
+        Some(Self { hi, lo })
+    }
+
+    /// `self - other`，要求 `self >= other`
+    fn sub(self, other: Self) -> Self {
+        let (lo, borrow) = self.lo.overflowing_sub(other.lo);
+        Self { hi: self.hi - other.hi - borrow as u128, lo }
+    }
+
+    /// 除以不超过 `u64::MAX` 的数，返回商和余数
+    fn div_small(self, divisor: u128) -> (Self, u128) {
+        let hi = self.hi / divisor;
+        let mut remainder = self.hi % divisor;
+        let mut lo = 0;
+        for shift in [64, 0] {
+            let part = (remainder << 64) | ((self.lo >> shift) & u64::MAX as u128);
+            lo |= (part / divisor) << shift;
+            remainder = part % divisor;
+        }
+        (Self { hi, lo }, remainder)
+    }
+}
+
+fn overflow() -> anyhow::Error {
+    anyhow!("numeric overflow: result exceeds the 38-digit precision of numeric expressions")
+}
+
+/// 比较两个值，任一为NULL时返回None。
+/// 数字按数值比较；日期与文本比较时按日期解析文本；其余按文本比较
+fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Null, _) | (_, Value::Null) => None,
+        (Value::Boolean(a), Value::Boolean(b)) => Some(a.cmp(b)),
+        (Value::DateTime { value: a, .. }, Value::DateTime { value: b, .. }) => Some(a.cmp(b)),
+        (Value::DateTimeTz { value: a, .. }, Value::DateTimeTz { value: b, .. }) => Some(a.cmp(b)),
+        (Value::DateTime { value, .. }, Value::Text(text)) => parse_datetime(text).map(|t| value.cmp(&t)),
+        (Value::Text(text), Value::DateTime { value, .. }) => parse_datetime(text).map(|t| t.cmp(value)),
+        (Value::DateTimeTz { value, .. }, Value::Text(text)) => {
+            parse_datetime(text).map(|t| value.naive_local().cmp(&t))
+        }
+        (Value::Text(text), Value::DateTimeTz { value, .. }) => {
+            parse_datetime(text).map(|t| t.cmp(&value.naive_local()))
+        }
+        (Value::Number(_), _) | (_, Value::Number(_)) => {
+            match (to_number(left), to_number(right)) {
+                (Ok(Some(a)), Ok(Some(b))) => Some(a.cmp(&b)),
+                _ => Some(left.to_string().cmp(&right.to_string())),
+            }
+        }
+        _ => Some(left.to_string().cmp(&right.to_string())),
+    }
+}
+
+fn parse_datetime(text: &str) -> Option<NaiveDateTime> {
+    let text = text.trim();
+    NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f")
+        .or_else(|_| NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S%.f"))
+        .ok()
+        .or_else(|| {
+            chrono::NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })
+}
+
+/// LIKE匹配：`%` 匹配任意个字符，`_` 匹配单个字符
+fn like(text: &[char], pattern: &[char]) -> bool {
+    let (mut t, mut p) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        // 先判断通配符，数据中的 `%` 不能按字面匹配掉模式中的 `%`
+        if p < pattern.len() && pattern[p] == '%' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && (pattern[p] == '_' || pattern[p] == text[t]) {
+            t += 1;
+            p += 1;
+        } else if let Some((bp, bt)) = backtrack {
+            p = bp + 1;
+            t = bt + 1;
+            backtrack = Some((bp, bt + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '%')
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Ident(String),
+    QuotedIdent(String),
+    Number(String),
+    Text(String),
+    Symbol(&'static str),
+}
+
+impl std::fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TokenKind::Ident(s) => write!(f, "{}", s),
+            TokenKind::QuotedIdent(s) => write!(f, "\"{}\"", s),
+            TokenKind::Number(s) => write!(f, "{}", s),
+            TokenKind::Text(s) => write!(f, "'{}'", s),
+            TokenKind::Symbol(s) => write!(f, "'{}'", s),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    /// 在表达式中的字符位置
+    offset: usize,
+}
+
+const SYMBOLS: &[&str] = &["<=", ">=", "!=", "<>", "==", "||", "=", "<", ">", "+", "-", "*", "/", "(", ")", ","];
+
+fn tokenize(text: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let ch = chars[i];
+        let offset = i;
+        if ch.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let kind = if ch.is_alphabetic() || ch == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || matches!(chars[i], '_' | '$' | '#')) {
+                i += 1;
+            }
+            TokenKind::Ident(chars[offset..i].iter().collect())
+        } else if ch.is_ascii_digit() || (ch == '.' && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit())) {
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            if i < chars.len() && chars[i] == '.' {
+                i += 1;
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+            }
+            TokenKind::Number(chars[offset..i].iter().collect())
+        } else if ch == '\'' || ch == '"' {
+            // 字符串用单引号，带引号的列名用双引号，连续两个引号表示引号本身
+            let mut value = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => bail!("unterminated {} starting at position {}", if ch == '\'' { "string" } else { "quoted name" }, offset + 1),
+                    Some(&c) if c == ch && chars.get(i + 1) == Some(&ch) => {
+                        value.push(ch);
+                        i += 2;
+                    }
+                    Some(&c) if c == ch => {
+                        i += 1;
+                        break;
+                    }
+                    Some(&c) => {
+                        value.push(c);
+                        i += 1;
+                    }
+                }
+            }
+            if ch == '\'' {
+                TokenKind::Text(value)
+            } else {
+                TokenKind::QuotedIdent(value)
+            }
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let Some(symbol) = SYMBOLS.iter().find(|s| rest.starts_with(**s)) else {
+                bail!("unexpected character {:?} at position {}", ch, offset + 1);
+            };
+            i += symbol.len();
+            TokenKind::Symbol(symbol)
+        };
+        tokens.push(Token { kind, offset });
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token> {
+        match self.tokens.get(self.pos) {
+            Some(token) => {
+                self.pos += 1;
+                Ok(token.clone())
+            }
+            None => bail!("unexpected end of expression"),
+        }
+    }
+
+    fn is_keyword(&self, offset: usize, keyword: &str) -> bool {
+        matches!(self.tokens.get(self.pos + offset), Some(Token { kind: TokenKind::Ident(s), .. }) if s.eq_ignore_ascii_case(keyword))
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.is_keyword(0, keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token { kind: TokenKind::Symbol(s), .. }) if *s == symbol);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<()> {
+        if self.eat_symbol(symbol) {
+            return Ok(());
+        }
+        match self.peek() {
+            Some(token) => bail!("expected '{}' at position {}, found {}", symbol, token.offset + 1, token.kind),
+            None => bail!("expected '{}' at end of expression", symbol),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_and()?;
+        while self.eat_keyword("OR") {
+            expr = Expr::Binary(BinaryOp::Or, Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_not()?;
+        while self.eat_keyword("AND") {
+            expr = Expr::Binary(BinaryOp::And, Box::new(expr), Box::new(self.parse_not()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr> {
+        if self.eat_keyword("NOT") {
+            return Ok(Expr::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_predicate()
+    }
+
+    fn parse_predicate(&mut self) -> Result<Expr> {
+        let expr = self.parse_concat()?;
+
+        if self.eat_keyword("IS") {
+            let negated = self.eat_keyword("NOT");
+            if !self.eat_keyword("NULL") {
+                bail!("expected NULL after IS{}", if negated { " NOT" } else { "" });
+            }
+            return Ok(Expr::IsNull { expr: Box::new(expr), negated });
+        }
+
+        let negated = (self.is_keyword(0, "NOT") && (self.is_keyword(1, "IN") || self.is_keyword(1, "LIKE")))
+            && self.eat_keyword("NOT");
+        if self.eat_keyword("IN") {
+            self.expect_symbol("(")?;
+            let mut list = vec![self.parse_concat()?];
+            while self.eat_symbol(",") {
+                list.push(self.parse_concat()?);
+            }
+            self.expect_symbol(")")?;
+            return Ok(Expr::In { expr: Box::new(expr), list, negated });
+        }
+        if self.eat_keyword("LIKE") {
+            let pattern = self.parse_concat()?;
+            return Ok(Expr::Like { expr: Box::new(expr), pattern: Box::new(pattern), negated });
+        }
+
+        let op = match self.peek().map(|t| &t.kind) {
+            Some(TokenKind::Symbol("=" | "==")) => BinaryOp::Eq,
+            Some(TokenKind::Symbol("!=" | "<>")) => BinaryOp::NotEq,
+            Some(TokenKind::Symbol("<")) => BinaryOp::Lt,
+            Some(TokenKind::Symbol("<=")) => BinaryOp::LtEq,
+            Some(TokenKind::Symbol(">")) => BinaryOp::Gt,
+            Some(TokenKind::Symbol(">=")) => BinaryOp::GtEq,
+            _ => return Ok(expr),
+        };
+        self.pos += 1;
+        let right = self.parse_concat()?;
+        Ok(Expr::Binary(op, Box::new(expr), Box::new(right)))
+    }
+
+    fn parse_concat(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_additive()?;
+        while self.eat_symbol("||") {
+            expr = Expr::Binary(BinaryOp::Concat, Box::new(expr), Box::new(self.parse_additive()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_term()?;
+        loop {
+            let op = if self.eat_symbol("+") {
+                BinaryOp::Add
+            } else if self.eat_symbol("-") {
+                BinaryOp::Sub
+            } else {
+                return Ok(expr);
+            };
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.parse_term()?));
+        }
+    }
+
+    fn parse_term(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_unary()?;
+        loop {
+            let op = if self.eat_symbol("*") {
+                BinaryOp::Mul
+            } else if self.eat_symbol("/") {
+                BinaryOp::Div
+            } else {
+                return Ok(expr);
+            };
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.parse_unary()?));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr> {
+        if self.eat_symbol("-") {
+            return Ok(Expr::Neg(Box::new(self.parse_unary()?)));
+        }
+        if self.eat_symbol("+") {
+            return self.parse_unary();
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr> {
+        let token = self.next()?;
+        let expr = match token.kind {
+            TokenKind::Number(n) => Expr::Literal(Value::Number(n)),
+            TokenKind::Text(s) => Expr::Literal(Value::Text(s)),
+            TokenKind::QuotedIdent(name) => Expr::Column { name, index: 0 },
+            TokenKind::Symbol("(") => {
+                let expr = self.parse_or()?;
+                self.expect_symbol(")")?;
+                expr
+            }
+            TokenKind::Ident(name) => {
+                if self.eat_symbol("(") {
+                    let Some(function) = Function::from_name(&name) else {
+                        bail!(
+                            "unknown function {} at position {} (functions: concat, upper, lower, trim, ltrim, rtrim, substr, length, replace, coalesce, nvl, round, abs)",
+                            name,
+                            token.offset + 1
+                        );
+                    };
+                    let mut args = Vec::new();
+                    if !self.eat_symbol(")") {
+                        args.push(self.parse_or()?);
+                        while self.eat_symbol(",") {
+                            args.push(self.parse_or()?);
+                        }
+                        self.expect_symbol(")")?;
+                    }
+                    let (min, max) = function.arity();
+                    if args.len() < min || args.len() > max {
+                        bail!("wrong number of arguments for {}: {}", name, args.len());
+                    }
+                    Expr::Call(function, args)
+                } else if name.eq_ignore_ascii_case("NULL") {
+                    Expr::Literal(Value::Null)
+                } else if name.eq_ignore_ascii_case("TRUE") {
+                    Expr::Literal(Value::Boolean(true))
+                } else if name.eq_ignore_ascii_case("FALSE") {
+                    Expr::Literal(Value::Boolean(false))
+                } else if ["AND", "OR", "NOT", "IS", "IN", "LIKE"].iter().any(|k| name.eq_ignore_ascii_case(k)) {
+                    bail!("unexpected {} at position {}", name, token.offset + 1);
+                } else {
+                    Expr::Column { name, index: 0 }
+                }
+            }
+            kind => bail!("unexpected {} at position {}", kind, token.offset + 1),
+        };
+        Ok(expr)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str, kind: ColumnKind) -> ColumnInfo {
+        ColumnInfo {
+            name: name.to_string(),
+            type_name: kind.name().to_uppercase(),
+            kind,
+            precision: None,
+            scale: None,
+            size: None,
+            nullable: true,
+        }
+    }
+
+    /// 以列 A（数字）、B（数字）、S（文本）求值
+    fn eval_row(text: &str, row: &[Value]) -> Result<Value> {
+        let columns = [
+            column("A", ColumnKind::Number),
+            column("B", ColumnKind::Number),
+            column("S", ColumnKind::Text),
+        ];
+        let mut expr = Expr::parse(text)?;
+        expr.bind(&columns)?;
+        expr.eval(row)
+    }
+
+    fn eval(text: &str) -> Value {
+        eval_row(text, &[Value::Null, Value::Null, Value::Null]).unwrap()
+    }
+
+    fn number(text: &str) -> Value {
+        Value::Number(text.to_string())
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(eval("1 + 2 * 3"), number("7"));
+        assert_eq!(eval("(1 + 2) * 3"), number("9"));
+        assert_eq!(eval("10 - 4 - 3"), number("3"));
+        assert_eq!(eval("-2 * 3 + 1"), number("-5"));
+        assert_eq!(eval("'a' || 1 + 2"), Value::Text("a3".to_string()));
+        assert_eq!(eval("1 = 1 OR 1 = 2 AND 1 = 2"), Value::Boolean(true));
+        assert_eq!(eval("NOT 1 = 2 AND 2 > 1"), Value::Boolean(true));
+    }
+
+    #[test]
+    fn three_valued_logic() {
+        assert_eq!(eval("A = 1"), Value::Null);
+        assert_eq!(eval("A = 1 AND 1 = 2"), Value::Boolean(false));
+        assert_eq!(eval("A = 1 AND 1 = 1"), Value::Null);
+        assert_eq!(eval("A = 1 OR 1 = 1"), Value::Boolean(true));
+        assert_eq!(eval("A = 1 OR 1 = 2"), Value::Null);
+        assert_eq!(eval("NOT A = 1"), Value::Null);
+        assert_eq!(eval("A IS NULL"), Value::Boolean(true));
+        assert_eq!(eval("A + 1"), Value::Null);
+        assert_eq!(eval("S || 'x'"), Value::Text("x".to_string()));
+    }
+
+    #[test]
+    fn like_patterns() {
+        assert_eq!(eval("'100%' LIKE '1%'"), Value::Boolean(true));
+        assert_eq!(eval("'%abc' LIKE '%c'"), Value::Boolean(true));
+        assert_eq!(eval("'a%b%c' LIKE 'a%c'"), Value::Boolean(true));
+        assert_eq!(eval("'%x' LIKE '%y'"), Value::Boolean(false));
+        assert_eq!(eval("'abc' LIKE 'a_c'"), Value::Boolean(true));
+        assert_eq!(eval("'abc' NOT LIKE 'a_'"), Value::Boolean(true));
+        assert_eq!(eval("'mississippi' LIKE '%iss%ppi'"), Value::Boolean(true));
+    }
+
+    #[test]
+    fn substr_and_round() {
+        assert_eq!(eval("substr('abcdef', 2, 3)"), Value::Text("bcd".to_string()));
+        assert_eq!(eval("substr('abcdef', 0, 2)"), Value::Text("ab".to_string()));
+        assert_eq!(eval("substr('abcdef', -2)"), Value::Text("ef".to_string()));
+        assert_eq!(eval("substr('abc', 5)"), Value::Null);
+        assert_eq!(eval("round(2.5)"), number("3"));
+        assert_eq!(eval("round(-2.5)"), number("-3"));
+        assert_eq!(eval("round(1234.5678, 2)"), number("1234.57"));
+        assert_eq!(eval("round(1234.5678, -2)"), number("1200"));
+        assert_eq!(eval("abs(-3.10)"), number("3.1"));
+    }
+
+    #[test]
+    fn exact_decimal_arithmetic() {
+        assert_eq!(eval("0.1 + 0.2"), number("0.3"));
+        assert_eq!(eval("12345678901234567890123 + 1"), number("12345678901234567890124"));
+        assert_eq!(eval("12345678901234567890123 < 12345678901234567890124"), Value::Boolean(true));
+        assert_eq!(eval("1 / 3"), number("0.33333333333333333333"));
+        assert_eq!(eval("2 / 3"), number("0.66666666666666666667"));
+        assert_eq!(eval("-7 / 2"), number("-3.5"));
+        assert!(eval_row("1 / 0", &[]).is_err());
+        assert!(eval_row("99999999999999999999999999999999999999 * 10", &[]).is_err());
+    }
+
+    #[test]
+    fn division_results_can_be_combined() {
+        let row = [number("10"), number("20"), Value::Null];
+        assert_eq!(
+            eval_row("(A / 3) * (B / 7)", &row).unwrap(),
+            number("9.5238095238095238095238095238095238095")
+        );
+        assert_eq!(eval_row("(A / 3) / (B / 7)", &row).unwrap(), number("1.16666666666666666666"));
+        assert_eq!(eval_row("(A / 3) + (B / 7)", &row).unwrap(), number("6.19047619047619047619"));
+        assert_eq!(eval_row("(A / 3) * 3", &row).unwrap(), number("9.99999999999999999999"));
+        assert_eq!(eval("-1 / 3"), number("-0.33333333333333333333"));
+        // 整数部分仍在范围内时舍入小数部分，整数部分超出时才报错
+        assert_eq!(
+            eval("100000000000000000000000000000000000 / 3 * 1000"),
+            number("33333333333333333333333333333333333333")
+        );
+        assert!(eval_row("100000000000000000000000000000000000 / 3 * 100000", &row).is_err());
+    }
+}
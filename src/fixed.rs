@@ -0,0 +1,416 @@
+use crate::config::{Alignment, ExportConfig, OverflowPolicy};
+use crate::db::{ColumnInfo, ColumnKind, Value};
+use crate::delimited::parse_escapes;
+use crate::encoding::{Transcoder, UTF8_MAX_CHAR_BYTES};
+use crate::format::ColumnFormatter;
+use crate::schema;
+use crate::sink;
+use crate::writer::RowWriter;
+use anyhow::{bail, Context, Result};
+use chrono::{FixedOffset, NaiveDate, TimeZone};
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::Path;
+use tracing::warn;
+
+/// 未指定精度的NUMBER的列宽（38位数字、符号及小数点）
+const UNBOUNDED_NUMBER_WIDTH: usize = 40;
+/// BINARY_FLOAT/BINARY_DOUBLE的列宽（如 `-1.7976931348623157E+308`）
+const FLOAT_WIDTH: usize = 24;
+/// 未指定小数位数的时间戳按微秒计算宽度
+const DEFAULT_FRACTION_DIGITS: u8 = 6;
+
+/// 定长记录中一列的位置及填充方式
+pub struct FieldLayout {
+    pub name: String,
+    pub type_name: String,
+    /// 起始位置（从1开始，按字节计算）
+    pub start: usize,
+    pub width: usize,
+    pub align: Alignment,
+    pub pad: u8,
+    pub overflow: OverflowPolicy,
+    /// 宽度来自配置（否则按列定义推算）
+    pub configured: bool,
+}
+
+/// 定长记录的布局
+pub struct Layout {
+    pub fields: Vec<FieldLayout>,
+    pub terminator: Vec<u8>,
+}
+
+impl Layout {
+    /// 按 `[export.fixed]` 配置确定各列的宽度，未配置宽度的列按列定义及格式化规则推算
+    pub fn new(config: &ExportConfig, columns: &[ColumnInfo], formatter: &ColumnFormatter) -> Result<Self> {
+        let fixed = &config.fixed;
+        for name in fixed.columns.keys() {
+            if !columns.iter().any(|c| c.name.eq_ignore_ascii_case(name)) {
+                bail!(
+                    "Column {} in [export.fixed.columns] not found in output columns (columns: {})",
+                    name,
+                    columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>().join(", ")
+                );
+            }
+        }
+
+        let transcoder = Transcoder::new(&config.encoding, config.unmappable)?;
+        let default_pad = pad_byte(&fixed.pad).context("Invalid export.fixed.pad")?;
+        let mut fields = Vec::with_capacity(columns.len());
+        let mut missing = Vec::new();
+        let mut start = 1;
+        for (i, column) in columns.iter().enumerate() {
+            let settings = fixed
+                .columns
+                .iter()
+                .find(|(name, _)| column.name.eq_ignore_ascii_case(name))
+                .map(|(_, settings)| settings);
+
+            let configured = settings.and_then(|s| s.width);
+            let width = match configured {
+                Some(width) => width,
+                None => match derived_width(column, i, formatter, transcoder.as_ref())? {
+                    Some(width) => width,
+                    None => {
+                        missing.push(format!("{} ({})", column.name, column.type_name));
+                        0
+                    }
+                },
+            };
+            let pad = match settings.and_then(|s| s.pad.as_deref()) {
+                Some(pad) => pad_byte(pad).with_context(|| format!("Invalid pad for column {}", column.name))?,
+                None => default_pad,
+            };
+            let align = settings.and_then(|s| s.align).unwrap_or(if column.kind.is_numeric() {
+                Alignment::Right
+            } else {
+                Alignment::Left
+            });
+
+            fields.push(FieldLayout {
+                name: column.name.clone(),
+                type_name: column.type_name.clone(),
+                start,
+                width,
+                align,
+                pad,
+                overflow: settings.and_then(|s| s.overflow).unwrap_or(fixed.overflow),
+                configured: configured.is_some(),
+            });
+            start += width;
+        }
+        if !missing.is_empty() {
+            bail!(
+                "Cannot derive fixed width for column(s) {} (set width in [export.fixed.columns])",
+                missing.join(", ")
+            );
+        }
+
+        Ok(Self {
+            fields,
+            terminator: parse_escapes(&config.record_terminator)?,
+        })
+    }
+
+    /// 记录长度（不含记录结束符）
+    pub fn record_length(&self) -> usize {
+        self.fields.iter().map(|f| f.width).sum()
+    }
+
+    /// 布局说明：每列的起止位置、宽度、对齐方式及填充字符
+    pub fn render(&self, config: &ExportConfig) -> String {
+        let data_name = Path::new(&config.output_file)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| config.output_file.clone());
+
+        let mut out = String::new();
+        let _ = writeln!(out, "# Fixed-width layout for {}", data_name);
+        let _ = writeln!(out, "# Encoding: {} (positions and widths in bytes)", config.encoding);
+        let _ = writeln!(
+            out,
+            "# Record length: {} bytes + terminator {:?}",
+            self.record_length(),
+            String::from_utf8_lossy(&self.terminator)
+        );
+        if config.include_header {
+            let _ = writeln!(out, "# First record is a header with column names");
+        }
+        let _ = writeln!(out, "# NULL values are written as spaces");
+        let _ = writeln!(out);
+
+        let name_width = self.fields.iter().map(|f| f.name.chars().count()).max().unwrap_or(0).max(6);
+        let _ = writeln!(
+            out,
+            "{:>3}  {:<name_width$}  {:>6}  {:>6}  {:>6}  {:<5}  {:<3}  {:<8}  Type",
+            "No", "Column", "Start", "End", "Width", "Align", "Pad", "Overflow"
+        );
+        for (i, field) in self.fields.iter().enumerate() {
+            let align = match field.align {
+                Alignment::Left => "left",
+                Alignment::Right => "right",
+            };
+            let overflow = match field.overflow {
+                OverflowPolicy::Error => "error",
+                OverflowPolicy::Truncate => "truncate",
+            };
+            let _ = writeln!(
+                out,
+                "{:>3}  {:<name_width$}  {:>6}  {:>6}  {:>6}  {:<5}  {:<3}  {:<8}  {}{}",
+                i + 1,
+                field.name,
+                field.start,
+                field.start + field.width - 1,
+                field.width,
+                align,
+                format!("'{}'", field.pad as char),
+                overflow,
+                field.type_name,
+                if field.configured { "" } else { " (derived)" }
+            );
+        }
+        out
+    }
+}
+
+/// 布局说明文件路径：`<输出文件名>.layout.txt`
+pub fn layout_path(config: &ExportConfig) -> String {
+    schema::sidecar_path(&config.output_file, "layout.txt")
+}
+
+/// 写入布局说明文件，返回文件路径；`columns` 为 [`writer::value_columns`](crate::writer::value_columns) 返回的列
+pub fn write_layout(config: &ExportConfig, columns: &[ColumnInfo], formatter: &ColumnFormatter) -> Result<String> {
+    let layout = Layout::new(config, columns, formatter)?;
+    let path = layout_path(config);
+    sink::write_file(config, &path, layout.render(config).as_bytes()).with_context(|| format!("Failed to write layout file: {}", path))?;
+    Ok(path)
+}
+
+/// 按列定义推算宽度：字符列为列长度乘以输出编码中单个字符的最大字节数，二进制列为编码后的长度，数字列为最大值（含符号）格式化后的长度，
+/// 日期/时间戳列为格式化后的长度；无法确定时返回None
+fn derived_width(
+    column: &ColumnInfo,
+    index: usize,
+    formatter: &ColumnFormatter,
+    transcoder: Option<&Transcoder>,
+) -> Result<Option<usize>> {
+    let sample = |value: Value| -> Result<Option<usize>> {
+        let text = formatter.format(index, value);
+        Ok(Some(encoded_len(transcoder, &text)?))
+    };
+    let date = NaiveDate::from_ymd_opt(2000, 12, 31)
+        .and_then(|d| d.and_hms_nano_opt(23, 59, 59, 999_999_999))
+        .unwrap_or_default();
+    let precision = column.scale.map(|s| s.clamp(0, 9) as u8).unwrap_or(DEFAULT_FRACTION_DIGITS);
+
+    match column.kind {
+        // 转换为Base64/十六进制的二进制列为ASCII文本，长度即字节数
+        ColumnKind::Text if is_binary_type(&column.type_name) => Ok(column.size.map(|size| size as usize)),
+        // 列长度为字符数（NVARCHAR2/NCHAR）或数据库字符集的字节数（VARCHAR2/CHAR），
+        // 字符数都不超过列长度，按输出编码中单个字符的最大字节数放大
+        ColumnKind::Text => {
+            let char_bytes = transcoder.map_or(UTF8_MAX_CHAR_BYTES, Transcoder::max_char_bytes);
+            Ok(column.size.map(|size| size as usize * char_bytes))
+        }
+        ColumnKind::Integer => match column.precision {
+            Some(p) => sample(Value::Number(format!("-{}", "9".repeat(p as usize)))),
+            None => sample(Value::Number(i64::MIN.to_string())),
+        },
+        ColumnKind::Number => match (column.precision, column.scale) {
+            (Some(p), Some(s)) if s < 0 => {
+                sample(Value::Number(format!("-{}{}", "9".repeat(p as usize), "0".repeat(s.unsigned_abs() as usize))))
+            }
+            (Some(p), Some(s)) if s as u32 >= p => sample(Value::Number(format!("-0.{}", "9".repeat(s as usize)))),
+            (Some(p), Some(s)) if s > 0 => sample(Value::Number(format!(
+                "-{}.{}",
+                "9".repeat((p - s as u32) as usize),
+                "9".repeat(s as usize)
+            ))),
+            (Some(p), Some(_)) => sample(Value::Number(format!("-{}", "9".repeat(p as usize)))),
+            _ => Ok(Some(UNBOUNDED_NUMBER_WIDTH)),
+        },
+        ColumnKind::Float => Ok(Some(FLOAT_WIDTH)),
+        ColumnKind::Date => sample(Value::DateTime { value: date, precision: 0 }),
+        ColumnKind::Timestamp => sample(Value::DateTime { value: date, precision }),
+        ColumnKind::TimestampTz => {
+            let offset = FixedOffset::west_opt(9 * 3600 + 30 * 60).context("Invalid sample offset")?;
+            match offset.from_local_datetime(&date).single() {
+                Some(value) => sample(Value::DateTimeTz { value, precision }),
+                None => Ok(None),
+            }
+        }
+        ColumnKind::Boolean => {
+            let t = sample(Value::Boolean(true))?;
+            let f = sample(Value::Boolean(false))?;
+            Ok(t.max(f))
+        }
+        ColumnKind::Binary | ColumnKind::Blob | ColumnKind::Clob | ColumnKind::Interval | ColumnKind::Other => {
+            Ok(None)
+        }
+    }
+}
+
+/// 数据库二进制类型（RAW、LONG RAW、BLOB、BFILE）
+fn is_binary_type(type_name: &str) -> bool {
+    type_name.starts_with("RAW") || matches!(type_name, "LONG RAW" | "BLOB" | "BFILE")
+}
+
+fn encoded_len(transcoder: Option<&Transcoder>, text: &str) -> Result<usize> {
+    match transcoder {
+        Some(transcoder) => {
+            let mut buf = Vec::new();
+            transcoder.encode(text, &mut buf)?;
+            Ok(buf.len())
+        }
+        None => Ok(text.len()),
+    }
+}
+
+/// 填充字符必须是单个ASCII字符，保证按字节计算的宽度不受编码影响
+fn pad_byte(pad: &str) -> Result<u8> {
+    match parse_escapes(pad)?.as_slice() {
+        [b] if b.is_ascii() => Ok(*b),
+        _ => bail!("pad must be a single ASCII character, got {:?}", pad),
+    }
+}
+
+/// 定长格式写入器：值按 `[export.columns]` 规则格式化后按列宽填充或截断
+pub struct FixedWidthWriter {
+    layout: Layout,
+    formatter: ColumnFormatter,
+    include_header: bool,
+    transcoder: Option<Transcoder>,
+    buffer: Vec<u8>,
+    field: Vec<u8>,
+    truncated: u64,
+}
+
+impl FixedWidthWriter {
+    pub fn new(config: &ExportConfig, layout: Layout, formatter: ColumnFormatter) -> Result<Self> {
+        let capacity = layout.record_length() + layout.terminator.len();
+        Ok(Self {
+            layout,
+            formatter,
+            include_header: config.include_header,
+            transcoder: Transcoder::new(&config.encoding, config.unmappable)?,
+            buffer: Vec::with_capacity(capacity),
+            field: Vec::with_capacity(256),
+            truncated: 0,
+        })
+    }
+
+    /// 按列宽截断（超出时）并填充后追加到记录
+    fn push_field(&mut self, index: usize, text: &str) -> Result<()> {
+        let field = &self.layout.fields[index];
+        let (width, align, pad, overflow) = (field.width, field.align, field.pad, field.overflow);
+        self.encode_field(text)?;
+        if self.field.len() > width {
+            if overflow == OverflowPolicy::Error {
+                bail!(
+                    "Value for column {} is {} bytes, exceeds width {}: {:?}",
+                    self.layout.fields[index].name,
+                    self.field.len(),
+                    width,
+                    text
+                );
+            }
+            let end = fitting_prefix(self.transcoder.as_ref(), text, width)?;
+            self.encode_field(&text[..end])?;
+            self.truncated += 1;
+        }
+
+        let padding = width - self.field.len();
+        match align {
+            Alignment::Left => {
+                self.buffer.extend_from_slice(&self.field);
+                self.buffer.resize(self.buffer.len() + padding, pad);
+            }
+            // 以0填充时符号在最前面，如 -000123
+            Alignment::Right if pad == b'0' && matches!(self.field.first(), Some(b'-' | b'+')) => {
+                self.buffer.push(self.field[0]);
+                self.buffer.resize(self.buffer.len() + padding, pad);
+                self.buffer.extend_from_slice(&self.field[1..]);
+            }
+            Alignment::Right => {
+                self.buffer.resize(self.buffer.len() + padding, pad);
+                self.buffer.extend_from_slice(&self.field);
+            }
+        }
+        Ok(())
+    }
+
+    fn encode_field(&mut self, text: &str) -> Result<()> {
+        self.field.clear();
+        match self.transcoder {
+            Some(ref transcoder) => transcoder.encode(text, &mut self.field),
+            None => {
+                self.field.extend_from_slice(text.as_bytes());
+                Ok(())
+            }
+        }
+    }
+}
+
+impl RowWriter for FixedWidthWriter {
+    /// 表头的列名超出列宽时截断
+    fn begin(&mut self, out: &mut dyn Write, headers: &[String]) -> Result<()> {
+        if !self.include_header {
+            return Ok(());
+        }
+        self.buffer.clear();
+        for (i, header) in headers.iter().enumerate() {
+            let width = self.layout.fields[i].width;
+            let end = fitting_prefix(self.transcoder.as_ref(), header, width)?;
+            self.encode_field(&header[..end])?;
+            self.buffer.extend_from_slice(&self.field);
+            self.buffer.resize(self.buffer.len() + width - self.field.len(), b' ');
+        }
+        self.buffer.extend_from_slice(&self.layout.terminator);
+        out.write_all(&self.buffer)?;
+        Ok(())
+    }
+
+    fn write_row(&mut self, out: &mut dyn Write, values: Vec<Value>) -> Result<()> {
+        self.buffer.clear();
+        for (i, value) in values.into_iter().enumerate() {
+            if matches!(value, Value::Null) {
+                let width = self.layout.fields[i].width;
+                self.buffer.resize(self.buffer.len() + width, b' ');
+                continue;
+            }
+            let text = self.formatter.format(i, value);
+            self.push_field(i, &text)?;
+        }
+        self.buffer.extend_from_slice(&self.layout.terminator);
+        out.write_all(&self.buffer)?;
+        Ok(())
+    }
+
+    fn finish(&mut self, _out: &mut dyn Write) -> Result<()> {
+        if self.truncated > 0 {
+            warn!("{} values longer than their column width were truncated", self.truncated);
+        }
+        Ok(())
+    }
+}
+
+/// 转换编码后不超过 `width` 字节的最长前缀（在 `text` 中的字节位置），不截断在字符中间
+fn fitting_prefix(transcoder: Option<&Transcoder>, text: &str, width: usize) -> Result<usize> {
+    let mut len = 0;
+    let mut buf = Vec::new();
+    for (i, c) in text.char_indices() {
+        let size = match transcoder {
+            Some(transcoder) => {
+                buf.clear();
+                transcoder.encode(c.encode_utf8(&mut [0u8; 4]), &mut buf)?;
+                buf.len()
+            }
+            None => c.len_utf8(),
+        };
+        if len + size > width {
+            return Ok(i);
+        }
+        len += size;
+    }
+    Ok(text.len())
+}
@@ -0,0 +1,302 @@
+use crate::config::{ColumnFormat, ColumnsConfig, TrimMode};
+use crate::db::{ColumnInfo, ColumnKind, Value};
+use anyhow::{anyhow, bail, Result};
+use chrono::format::{Item, StrftimeItems};
+use chrono::NaiveDate;
+use std::fmt::Write;
+
+/// 可在 `[export.columns.types]` 中配置规则的列类型
+pub const FORMAT_TYPES: &[ColumnKind] = &[
+    ColumnKind::Text,
+    ColumnKind::Integer,
+    ColumnKind::Number,
+    ColumnKind::Float,
+    ColumnKind::Date,
+    ColumnKind::Timestamp,
+    ColumnKind::TimestampTz,
+    ColumnKind::Boolean,
+];
+
+/// 作为标志值识别为真/假的文本（不区分大小写）
+const TRUE_FLAGS: &[&str] = &["1", "y", "yes", "t", "true"];
+const FALSE_FLAGS: &[&str] = &["0", "n", "no", "f", "false"];
+
+pub fn kind_from_name(name: &str) -> Option<ColumnKind> {
+    FORMAT_TYPES.iter().copied().find(|kind| kind.name() == name)
+}
+
+/// 检查规则本身的取值（与列类型无关）
+pub fn check_syntax(rule: &ColumnFormat) -> std::result::Result<(), String> {
+    if let Some(ref format) = rule.format
+        && StrftimeItems::new(format).any(|item| matches!(item, Item::Error))
+    {
+        return Err(format!("invalid date format {:?}", format));
+    }
+    if rule.precision == Some(0) {
+        return Err("precision must be greater than 0".to_string());
+    }
+    Ok(())
+}
+
+/// 检查规则是否适用于该类型的列；标志值映射仅在按列名配置时可用于非布尔列
+pub fn check_rule(rule: &ColumnFormat, kind: ColumnKind, by_name: bool) -> std::result::Result<(), String> {
+    check_syntax(rule)?;
+
+    if let Some(ref format) = rule.format {
+        if !kind.is_temporal() {
+            return Err(format!("format only applies to date/timestamp columns, not {}", kind.name()));
+        }
+        // 时区相关的格式（如 %z）不能用于不带时区的列，格式化时会失败
+        let sample = NaiveDate::from_ymd_opt(2000, 1, 1)
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .unwrap_or_default();
+        let mut out = String::new();
+        let ok = if kind == ColumnKind::TimestampTz {
+            write!(out, "{}", sample.and_utc().fixed_offset().format(format)).is_ok()
+        } else {
+            write!(out, "{}", sample.format(format)).is_ok()
+        };
+        if !ok {
+            return Err(format!("format {:?} cannot be used for {} columns", format, kind.name()));
+        }
+    }
+
+    let number_rule = rule.precision.is_some()
+        || rule.scale.is_some()
+        || rule.thousands_separator.is_some()
+        || rule.decimal_separator.is_some();
+    if number_rule && !kind.is_numeric() {
+        return Err(format!(
+            "precision/scale/separators only apply to number columns, not {}",
+            kind.name()
+        ));
+    }
+
+    let flag_rule = rule.true_value.is_some() || rule.false_value.is_some();
+    if flag_rule && !by_name && kind != ColumnKind::Boolean {
+        return Err(format!(
+            "true_value/false_value only apply to boolean columns or named columns, not {}",
+            kind.name()
+        ));
+    }
+    Ok(())
+}
+
+/// 按 `[export.columns]` 规则将字段值格式化为文本
+pub struct ColumnFormatter {
+    rules: Vec<Option<ColumnFormat>>,
+}
+
+impl ColumnFormatter {
+    pub fn new(config: &ColumnsConfig, columns: &[ColumnInfo]) -> Result<Self> {
+        for name in config.names.keys() {
+            if !columns.iter().any(|c| c.name.eq_ignore_ascii_case(name)) {
+                bail!(
+                    "Column {} in [export.columns.names] not found in query result (columns: {})",
+                    name,
+                    columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>().join(", ")
+                );
+            }
+        }
+
+        let mut rules = Vec::with_capacity(columns.len());
+        for column in columns {
+            let by_type = config.types.get(column.kind.name());
+            let by_name = config
+                .names
+                .iter()
+                .find(|(name, _)| column.name.eq_ignore_ascii_case(name))
+                .map(|(_, rule)| rule);
+
+            let rule = match (by_type, by_name) {
+                (None, None) => None,
+                (Some(rule), None) => Some(rule.clone()),
+                (None, Some(rule)) => Some(rule.clone()),
+                (Some(base), Some(rule)) => Some(merge(base, rule)),
+            };
+            if let Some(ref rule) = rule {
+                check_rule(rule, column.kind, by_name.is_some())
+                    .map_err(|e| anyhow!("Invalid format for column {}: {}", column.name, e))?;
+            }
+            rules.push(rule);
+        }
+
+        Ok(Self { rules })
+    }
+
+    /// 第 `index` 列合并后的规则
+    pub fn rule(&self, index: usize) -> Option<&ColumnFormat> {
+        self.rules.get(index).and_then(Option::as_ref)
+    }
+
+    pub fn format_row(&self, values: Vec<Value>) -> Vec<String> {
+        values.into_iter().enumerate().map(|(i, value)| self.format(i, value)).collect()
+    }
+
+    /// 按第 `index` 列的规则格式化单个值
+    pub fn format(&self, index: usize, value: Value) -> String {
+        match self.rule(index) {
+            Some(rule) => format_value(value, rule),
+            None => value.into_string(),
+        }
+    }
+}
+
+/// 列名规则中指定的项覆盖类型规则
+fn merge(base: &ColumnFormat, rule: &ColumnFormat) -> ColumnFormat {
+    ColumnFormat {
+        format: rule.format.clone().or_else(|| base.format.clone()),
+        precision: rule.precision.or(base.precision),
+        scale: rule.scale.or(base.scale),
+        thousands_separator: rule.thousands_separator.clone().or_else(|| base.thousands_separator.clone()),
+        decimal_separator: rule.decimal_separator.clone().or_else(|| base.decimal_separator.clone()),
+        trim: rule.trim.or(base.trim),
+        true_value: rule.true_value.clone().or_else(|| base.true_value.clone()),
+        false_value: rule.false_value.clone().or_else(|| base.false_value.clone()),
+    }
+}
+
+fn format_value(value: Value, rule: &ColumnFormat) -> String {
+    let text = match (value, rule.format.as_deref()) {
+        (Value::Null, _) => return String::new(),
+        // 格式已在ColumnFormatter::new中校验，格式化不会失败
+        (Value::DateTime { value, .. }, Some(format)) => {
+            let mut out = String::new();
+            let _ = write!(out, "{}", value.format(format));
+            out
+        }
+        (Value::DateTimeTz { value, .. }, Some(format)) => {
+            let mut out = String::new();
+            let _ = write!(out, "{}", value.format(format));
+            out
+        }
+        (Value::Number(text), _) => format_number(&text, rule).unwrap_or(text),
+        (value @ Value::Boolean(b), _) => {
+            let mapped = if b { &rule.true_value } else { &rule.false_value };
+            return match mapped {
+                Some(mapped) => mapped.clone(),
+                None => value.into_string(),
+            };
+        }
+        (other, _) => other.into_string(),
+    };
+
+    let text = match rule.trim {
+        Some(TrimMode::Left) => text.trim_start().to_string(),
+        Some(TrimMode::Right) => text.trim_end().to_string(),
+        Some(TrimMode::Both) => text.trim().to_string(),
+        None => text,
+    };
+
+    // 非布尔列按标志值（Y/N、1/0等）映射，无法识别的值保持原样
+    if let Some(ref mapped) = rule.true_value
+        && TRUE_FLAGS.iter().any(|flag| text.eq_ignore_ascii_case(flag))
+    {
+        return mapped.clone();
+    }
+    if let Some(ref mapped) = rule.false_value
+        && FALSE_FLAGS.iter().any(|flag| text.eq_ignore_ascii_case(flag))
+    {
+        return mapped.clone();
+    }
+    text
+}
+
+/// 按精度、小数位数和分隔符格式化十进制文本，在文本上直接舍入以避免浮点误差；
+/// 无法解析时返回None
+fn format_number(text: &str, rule: &ColumnFormat) -> Option<String> {
+    if rule.precision.is_none()
+        && rule.scale.is_none()
+        && rule.thousands_separator.is_none()
+        && rule.decimal_separator.is_none()
+    {
+        return None;
+    }
+
+    let (negative, body) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let (int_part, frac_part) = body.split_once('.').unwrap_or((body, ""));
+    if int_part.is_empty() && frac_part.is_empty()
+        || !int_part.bytes().chain(frac_part.bytes()).all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let mut digits: Vec<u8> = int_part.bytes().chain(frac_part.bytes()).map(|b| b - b'0').collect();
+    let mut int_len = int_part.len();
+
+    if let Some(precision) = rule.precision
+        && let Some(first) = digits.iter().position(|&d| d != 0)
+    {
+        round_at(&mut digits, &mut int_len, first + precision as usize);
+    }
+    match rule.scale {
+        Some(scale) => {
+            let keep = int_len + scale as usize;
+            round_at(&mut digits, &mut int_len, keep);
+            digits.resize(int_len + scale as usize, 0);
+        }
+        None if rule.precision.is_some() => {
+            while digits.len() > int_len && digits.last() == Some(&0) {
+                digits.pop();
+            }
+        }
+        None => {}
+    }
+
+    let int_digits = &digits[..int_len];
+    let frac_digits = &digits[int_len..];
+    let start = int_digits.iter().position(|&d| d != 0).unwrap_or(int_digits.len());
+    let int_digits = if start == int_digits.len() { &[0u8][..] } else { &int_digits[start..] };
+
+    let mut out = String::with_capacity(text.len() + 8);
+    if negative && digits.iter().any(|&d| d != 0) {
+        out.push('-');
+    }
+    for (i, d) in int_digits.iter().enumerate() {
+        if i > 0
+            && (int_digits.len() - i).is_multiple_of(3)
+            && let Some(ref separator) = rule.thousands_separator
+        {
+            out.push_str(separator);
+        }
+        out.push((b'0' + d) as char);
+    }
+    if !frac_digits.is_empty() {
+        out.push_str(rule.decimal_separator.as_deref().unwrap_or("."));
+        out.extend(frac_digits.iter().map(|d| (b'0' + d) as char));
+    }
+    Some(out)
+}
+
+/// 保留前 `keep` 位数字并四舍五入（远离零），整数部分被舍去的位补零
+fn round_at(digits: &mut Vec<u8>, int_len: &mut usize, keep: usize) {
+    if keep >= digits.len() {
+        return;
+    }
+    let round_up = digits[keep] >= 5;
+    digits.truncate(keep);
+
+    if round_up {
+        let mut i = keep;
+        loop {
+            if i == 0 {
+                digits.insert(0, 1);
+                *int_len += 1;
+                break;
+            }
+            i -= 1;
+            if digits[i] == 9 {
+                digits[i] = 0;
+            } else {
+                digits[i] += 1;
+                break;
+            }
+        }
+    }
+    if digits.len() < *int_len {
+        digits.resize(*int_len, 0);
+    }
+}
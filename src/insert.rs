@@ -0,0 +1,402 @@
+use crate::config::{CompressionType, ExportConfig, SqlDialect};
+use crate::db::Value;
+use crate::delimited::is_numeric;
+use crate::encoding::Transcoder;
+use crate::writer::RowWriter;
+use anyhow::{bail, Context, Result};
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::Path;
+
+/// SQL*Plus单行不能超过2499个字符：较长的字符串字面量分段拼接，每行最多此字节数
+const ORACLE_LINE_BYTES: usize = 1000;
+/// Oracle字符串字面量的最大字节数，超出时按CLOB拼接
+const ORACLE_LITERAL_BYTES: usize = 4000;
+/// Oracle RAW字面量的最大字节数
+const ORACLE_RAW_BYTES: usize = 2000;
+/// Oracle方言下超过此长度的列清单或值清单按每项一行写出
+const ORACLE_LINE_LENGTH: usize = 1000;
+/// 一条 `INSERT ALL` 最多包含的列数（各行列数之和，ORA-24335）
+const ORACLE_INSERT_ALL_COLUMNS: usize = 999;
+
+/// INSERT语句写入器，每 `batch_size` 行合并为一条语句，整个脚本在一个事务中执行
+pub struct InsertWriter {
+    dialect: SqlDialect,
+    table: String,
+    batch_size: usize,
+    transcoder: Option<Transcoder>,
+    headers: Vec<String>,
+    /// `(列1, 列2, ...)`
+    column_list: String,
+    /// 当前批次中各行的 `(值1, 值2, ...)`
+    rows: Vec<String>,
+    buffer: Vec<u8>,
+}
+
+impl InsertWriter {
+    pub fn from_config(config: &ExportConfig) -> Result<Self> {
+        let dialect = config.sql.dialect;
+        let table = match (&config.sql.table, &config.source_table) {
+            (Some(table), _) => table.clone(),
+            (None, Some(table)) => format!("{}.{}", identifier(dialect, &table.owner), identifier(dialect, &table.name)),
+            (None, None) => identifier(dialect, &file_table_name(config)),
+        };
+        Ok(Self {
+            dialect,
+            table,
+            batch_size: config.sql.batch_size.max(1),
+            transcoder: Transcoder::new(&config.encoding, config.unmappable)?,
+            headers: Vec::new(),
+            column_list: String::new(),
+            rows: Vec::with_capacity(config.sql.batch_size.clamp(1, 1000)),
+            buffer: Vec::with_capacity(64 * 1024),
+        })
+    }
+
+    fn write_text(&mut self, out: &mut dyn Write, text: &str) -> Result<()> {
+        self.buffer.clear();
+        match self.transcoder {
+            Some(ref transcoder) => transcoder.encode(text, &mut self.buffer)?,
+            None => self.buffer.extend_from_slice(text.as_bytes()),
+        }
+        out.write_all(&self.buffer)?;
+        Ok(())
+    }
+
+    /// 将当前批次写为一条INSERT语句
+    fn flush(&mut self, out: &mut dyn Write) -> Result<()> {
+        if self.rows.is_empty() {
+            return Ok(());
+        }
+        let mut statement = String::new();
+        if self.rows.len() == 1 {
+            writeln!(statement, "INSERT INTO {} {} VALUES {};", self.table, self.column_list, self.rows[0])?;
+        } else if self.dialect == SqlDialect::Oracle {
+            // Oracle 23ai之前不支持多行VALUES
+            writeln!(statement, "INSERT ALL")?;
+            for row in &self.rows {
+                writeln!(statement, "  INTO {} {} VALUES {}", self.table, self.column_list, row)?;
+            }
+            writeln!(statement, "SELECT 1 FROM DUAL;")?;
+        } else {
+            writeln!(statement, "INSERT INTO {} {} VALUES", self.table, self.column_list)?;
+            for (i, row) in self.rows.iter().enumerate() {
+                let end = if i + 1 < self.rows.len() { "," } else { ";" };
+                writeln!(statement, "  {}{}", row, end)?;
+            }
+        }
+        self.rows.clear();
+        self.write_text(out, &statement)
+    }
+
+    fn literal(&self, column: usize, value: Value, out: &mut String) -> Result<()> {
+        let dialect = self.dialect;
+        match value {
+            Value::Null => out.push_str("NULL"),
+            Value::Text(text) => string_literal(dialect, &text, out)
+                .with_context(|| format!("Invalid value in column {}", self.headers[column]))?,
+            Value::Number(text) => match special_number(&text) {
+                None if is_numeric(text.as_bytes()) => out.push_str(&text),
+                None => string_literal(dialect, &text, out)?,
+                Some(special) => match (dialect, special) {
+                    (SqlDialect::Oracle, "inf") => out.push_str("BINARY_DOUBLE_INFINITY"),
+                    (SqlDialect::Oracle, "-inf") => out.push_str("-BINARY_DOUBLE_INFINITY"),
+                    (SqlDialect::Oracle, _) => out.push_str("BINARY_DOUBLE_NAN"),
+                    (SqlDialect::Postgres, "inf") => out.push_str("'Infinity'::float8"),
+                    (SqlDialect::Postgres, "-inf") => out.push_str("'-Infinity'::float8"),
+                    (SqlDialect::Postgres, _) => out.push_str("'NaN'::float8"),
+                    (SqlDialect::Mysql, _) => bail!(
+                        "Column {} contains {}, which MySQL cannot store",
+                        self.headers[column],
+                        text
+                    ),
+                },
+            },
+            Value::Boolean(b) => out.push_str(match (dialect, b) {
+                (SqlDialect::Oracle, true) => "1",
+                (SqlDialect::Oracle, false) => "0",
+                (_, true) => "TRUE",
+                (_, false) => "FALSE",
+            }),
+            ref datetime @ Value::DateTime { precision, .. } => {
+                let text = datetime.to_string();
+                match dialect {
+                    SqlDialect::Oracle if precision == 0 => {
+                        write!(out, "TO_DATE('{}', 'YYYY-MM-DD HH24:MI:SS')", text)?
+                    }
+                    SqlDialect::Oracle => write!(out, "TO_TIMESTAMP('{}', 'YYYY-MM-DD HH24:MI:SS.FF')", text)?,
+                    SqlDialect::Postgres => write!(out, "TIMESTAMP '{}'", text)?,
+                    SqlDialect::Mysql => write!(out, "'{}'", text)?,
+                }
+            }
+            ref datetime @ Value::DateTimeTz { precision, .. } => {
+                let text = datetime.to_string();
+                match dialect {
+                    SqlDialect::Oracle => {
+                        let fraction = if precision > 0 { ".FF" } else { "" };
+                        write!(out, "TO_TIMESTAMP_TZ('{}', 'YYYY-MM-DD HH24:MI:SS{} TZH:TZM')", text, fraction)?
+                    }
+                    SqlDialect::Postgres => write!(out, "TIMESTAMPTZ '{}'", text)?,
+                    // MySQL 8.0.19起支持带偏移量的字面量，偏移量前不能有空格
+                    SqlDialect::Mysql => write!(out, "'{}'", text.replacen(" +", "+", 1).replacen(" -", "-", 1))?,
+                }
+            }
+            Value::Binary(ref bytes) => match dialect {
+                SqlDialect::Oracle if bytes.len() > ORACLE_RAW_BYTES => bail!(
+                    "Column {} has {} bytes, more than an Oracle RAW literal can hold ({} bytes); set blob = \"file\" to write it to a separate file",
+                    self.headers[column],
+                    bytes.len(),
+                    ORACLE_RAW_BYTES
+                ),
+                SqlDialect::Oracle => {
+                    // 十六进制文本分段拼接，避免超过SQL*Plus的单行长度
+                    let hex = value.to_string();
+                    let pieces: Vec<String> = hex
+                        .as_bytes()
+                        .chunks(ORACLE_LINE_BYTES)
+                        .map(|chunk| format!("'{}'", String::from_utf8_lossy(chunk)))
+                        .collect();
+                    write!(out, "HEXTORAW({})", pieces.join(" ||\n"))?
+                }
+                SqlDialect::Postgres => write!(out, "decode('{}', 'hex')", value)?,
+                SqlDialect::Mysql => write!(out, "X'{}'", value)?,
+            },
+            // LobWriter已将临时文件替换为附属文件路径
+            Value::BlobFile(_) => bail!("Unexpected unprocessed LOB file in column {}", self.headers[column]),
+        }
+        Ok(())
+    }
+}
+
+impl RowWriter for InsertWriter {
+    fn begin(&mut self, out: &mut dyn Write, headers: &[String]) -> Result<()> {
+        self.headers = headers.to_vec();
+        let names: Vec<String> = headers.iter().map(|name| identifier(self.dialect, name)).collect();
+        self.column_list = format!("({})", join(self.dialect, &names));
+        if self.dialect == SqlDialect::Oracle {
+            self.batch_size = self.batch_size.min((ORACLE_INSERT_ALL_COLUMNS / headers.len().max(1)).max(1));
+        }
+
+        let prologue = match self.dialect {
+            // 按SQL*Plus脚本执行：关闭 & 变量替换，允许字符串中的空行
+            SqlDialect::Oracle => "SET DEFINE OFF\nSET SQLBLANKLINES ON\n",
+            SqlDialect::Postgres => "BEGIN;\n",
+            SqlDialect::Mysql => "START TRANSACTION;\n",
+        };
+        self.write_text(out, prologue)
+    }
+
+    fn write_row(&mut self, out: &mut dyn Write, values: Vec<Value>) -> Result<()> {
+        let mut literals = Vec::with_capacity(values.len());
+        for (i, value) in values.into_iter().enumerate() {
+            let mut literal = String::new();
+            self.literal(i, value, &mut literal)?;
+            literals.push(literal);
+        }
+        self.rows.push(format!("({})", join(self.dialect, &literals)));
+        if self.rows.len() >= self.batch_size {
+            self.flush(out)?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, out: &mut dyn Write) -> Result<()> {
+        self.flush(out)?;
+        self.write_text(out, "COMMIT;\n")
+    }
+}
+
+/// 逗号连接，Oracle方言下过长时每项一行
+fn join(dialect: SqlDialect, items: &[String]) -> String {
+    let length: usize = items.iter().map(|item| item.len() + 2).sum();
+    if dialect == SqlDialect::Oracle && length > ORACLE_LINE_LENGTH {
+        format!("\n    {}\n  ", items.join(",\n    "))
+    } else {
+        items.join(", ")
+    }
+}
+
+/// 标识符：Oracle原样加双引号；PostgreSQL/MySQL中全大写的普通名称转为小写（与其默认的小写名称一致）
+fn identifier(dialect: SqlDialect, name: &str) -> String {
+    let plain = name.starts_with(|c: char| c.is_ascii_uppercase())
+        && name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+    match dialect {
+        SqlDialect::Oracle => format!("\"{}\"", name.replace('"', "\"\"")),
+        SqlDialect::Postgres if plain => format!("\"{}\"", name.to_lowercase()),
+        SqlDialect::Postgres => format!("\"{}\"", name.replace('"', "\"\"")),
+        SqlDialect::Mysql if plain => format!("`{}`", name.to_lowercase()),
+        SqlDialect::Mysql => format!("`{}`", name.replace('`', "``")),
+    }
+}
+
+/// 查询模式下以输出文件名（不含扩展名）作为表名
+fn file_table_name(config: &ExportConfig) -> String {
+    let mut path = Path::new(&config.output_file).to_path_buf();
+    if matches!(config.compression, CompressionType::Gzip) {
+        path.set_extension("");
+    }
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().to_uppercase())
+        .unwrap_or_default()
+}
+
+/// BINARY_FLOAT/BINARY_DOUBLE的特殊值
+fn special_number(text: &str) -> Option<&'static str> {
+    match text.to_ascii_lowercase().as_str() {
+        "inf" | "+inf" | "infinity" | "+infinity" => Some("inf"),
+        "-inf" | "-infinity" => Some("-inf"),
+        "nan" => Some("nan"),
+        _ => None,
+    }
+}
+
+fn string_literal(dialect: SqlDialect, text: &str, out: &mut String) -> Result<()> {
+    match dialect {
+        SqlDialect::Oracle => oracle_string(text, out),
+        SqlDialect::Postgres => {
+            if text.contains('\0') {
+                bail!("PostgreSQL text cannot contain NUL characters");
+            }
+            write!(out, "'{}'", text.replace('\'', "''"))?;
+        }
+        SqlDialect::Mysql => {
+            out.push('\'');
+            for ch in text.chars() {
+                match ch {
+                    '\'' => out.push_str("''"),
+                    '\\' => out.push_str("\\\\"),
+                    '\0' => out.push_str("\\0"),
+                    // Windows下mysql客户端将Ctrl+Z视为文件结束
+                    '\x1a' => out.push_str("\\Z"),
+                    other => out.push(other),
+                }
+            }
+            out.push('\'');
+        }
+    }
+    Ok(())
+}
+
+/// 较长的字符串分段拼接并换行，超过4000字节时以 `TO_CLOB` 开头按CLOB拼接
+fn oracle_string(text: &str, out: &mut String) {
+    let pieces = oracle_pieces(text);
+    let mut line = 0;
+    for (i, piece) in pieces.iter().enumerate() {
+        if i > 0 {
+            if line + piece.len() + 4 > ORACLE_LINE_BYTES {
+                out.push_str(" ||\n");
+                line = 0;
+            } else {
+                out.push_str(" || ");
+                line += 4;
+            }
+        }
+        if i == 0 && text.len() > ORACLE_LITERAL_BYTES {
+            out.push_str("TO_CLOB(");
+            out.push_str(piece);
+            out.push(')');
+        } else {
+            out.push_str(piece);
+        }
+        line += piece.len();
+    }
+}
+
+/// 拆分为不超过 `ORACLE_LINE_BYTES` 的带引号片段；回车换行写为 `CHR(13)`/`CHR(10)`，
+/// 避免值中只有 `/` 或 `.` 的行被SQL*Plus当作执行或结束输入
+fn oracle_pieces(text: &str) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut current = String::new();
+    let close = |current: &mut String, pieces: &mut Vec<String>| {
+        if !current.is_empty() {
+            pieces.push(format!("'{}'", current));
+            current.clear();
+        }
+    };
+    for ch in text.chars() {
+        match ch {
+            '\r' | '\n' => {
+                close(&mut current, &mut pieces);
+                pieces.push(if ch == '\r' { "CHR(13)" } else { "CHR(10)" }.to_string());
+            }
+            _ => {
+                let escaped = if ch == '\'' { 2 } else { ch.len_utf8() };
+                if current.len() + escaped + 2 > ORACLE_LINE_BYTES {
+                    close(&mut current, &mut pieces);
+                }
+                current.push(ch);
+                if ch == '\'' {
+                    current.push('\'');
+                }
+            }
+        }
+    }
+    close(&mut current, &mut pieces);
+    if pieces.is_empty() {
+        pieces.push("''".to_string());
+    }
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn oracle_script(columns: usize, rows: usize, value: &str) -> String {
+        let mut config = ExportConfig::default();
+        config.sql.dialect = SqlDialect::Oracle;
+        config.sql.table = Some("T".to_string());
+        let mut writer = InsertWriter::from_config(&config).unwrap();
+        let headers: Vec<String> = (0..columns).map(|i| format!("C{}", i)).collect();
+        let mut out = Vec::new();
+        writer.begin(&mut out, &headers).unwrap();
+        for _ in 0..rows {
+            let values = (0..columns).map(|_| Value::Text(value.to_string())).collect();
+            writer.write_row(&mut out, values).unwrap();
+        }
+        writer.finish(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn insert_all_stays_within_column_limit() {
+        let script = oracle_script(300, 10, "x");
+        // 每条INSERT ALL最多3行（900列）
+        assert_eq!(script.matches("INSERT ALL").count(), 3);
+        assert_eq!(script.matches("INSERT INTO").count(), 1);
+        assert_eq!(script.matches("  INTO ").count(), 9);
+    }
+
+    #[test]
+    fn long_strings_are_split_into_short_lines() {
+        let value = "'".repeat(3000) + &"\n".repeat(500) + &"é".repeat(3000);
+        let script = oracle_script(1, 1, &value);
+        // SQL*Plus单行最多2499个字符
+        assert!(script.lines().all(|line| line.len() < 1100), "line too long");
+        assert!(script.contains("\n    TO_CLOB('''"));
+    }
+
+    #[test]
+    fn line_breaks_become_chr() {
+        let mut out = String::new();
+        oracle_string("a\r\n/\nb'c", &mut out);
+        assert_eq!(out, "'a' || CHR(13) || CHR(10) || '/' || CHR(10) || 'b''c'");
+
+        out.clear();
+        oracle_string("", &mut out);
+        assert_eq!(out, "''");
+    }
+
+    #[test]
+    fn long_raw_is_split() {
+        let mut config = ExportConfig::default();
+        config.sql.dialect = SqlDialect::Oracle;
+        let mut writer = InsertWriter::from_config(&config).unwrap();
+        writer.headers = vec!["B".to_string()];
+        let mut out = String::new();
+        writer.literal(0, Value::Binary(vec![0xAB; ORACLE_RAW_BYTES]), &mut out).unwrap();
+        assert!(out.starts_with("HEXTORAW('ABAB"));
+        assert_eq!(out.lines().count(), 4);
+        assert!(out.lines().all(|line| line.len() < 1100));
+    }
+}
@@ -0,0 +1,303 @@
+use crate::config::{Config, ConfigSource};
+use crate::validate::Validator;
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use toml::{Table, Value};
+
+/// 环境变量前缀，如 `EL_DATABASE_PASSWORD` 对应 `database.password`
+const ENV_PREFIX: &str = "EL_";
+
+/// 分层配置：默认值 < 配置文件 < 环境变量 < 命令行参数
+///
+/// 各层按TOML键路径（如 `export.format`）合并，并记录每个配置项最终来自哪一层。
+pub struct ConfigLayers {
+    defaults: Table,
+    merged: Table,
+    sources: BTreeMap<String, ConfigSource>,
+    /// 无法对应到配置项的 `EL_` 环境变量
+    unknown_env: Vec<String>,
+}
+
+impl ConfigLayers {
+    pub fn new() -> Result<Self> {
+        let defaults = Table::try_from(Config::default()).context("Failed to serialize default config")?;
+        let mut sources = BTreeMap::new();
+        collect_leaf_keys(&defaults, "", &mut |key| {
+            sources.insert(key, ConfigSource::Default);
+        });
+
+        Ok(Self {
+            merged: defaults.clone(),
+            defaults,
+            sources,
+            unknown_env: Vec::new(),
+        })
+    }
+
+    /// 合并配置文件
+    pub fn merge_file(&mut self, path: &str) -> Result<()> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {}", path))?;
+        let table: Table = content
+            .parse()
+            .with_context(|| format!("Invalid config file: {}", path))?;
+
+        let source = ConfigSource::File(path.to_string());
+        let mut keys = Vec::new();
+        collect_leaf_keys(&table, "", &mut |key| keys.push(key));
+        for key in keys {
+            if let Some(value) = get_path(&table, &key) {
+                self.set(&key, value.clone(), source.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// 合并 `EL_<SECTION>_<KEY>` 形式的环境变量
+    pub fn merge_env(&mut self) {
+        let mut vars: Vec<(String, String)> = std::env::vars()
+            .filter(|(name, _)| name.starts_with(ENV_PREFIX))
+            .collect();
+        vars.sort();
+
+        for (name, raw) in vars {
+            let Some(key) = self.env_key(&name) else {
+                self.unknown_env.push(name);
+                continue;
+            };
+            let value = self.env_value(&key, &raw);
+            self.set(&key, value, ConfigSource::Env(name));
+        }
+    }
+
+    /// 将环境变量名映射为配置键，按默认配置的嵌套结构逐级匹配，
+    /// 如 `EL_EXPORT_OUTPUT_FILE` => `export.output_file`，`EL_EXPORT_LOB_MAX_LENGTH` => `export.lob.max_length`
+    fn env_key(&self, name: &str) -> Option<String> {
+        let rest = name.strip_prefix(ENV_PREFIX)?.to_lowercase();
+        self.defaults.iter().find_map(|(section, value)| {
+            let field = rest.strip_prefix(section.as_str())?.strip_prefix('_')?;
+            let table = value.as_table()?;
+            (!field.is_empty()).then(|| format!("{}.{}", section, nested_key(table, field)))
+        })
+    }
+
+    /// 按默认值的类型解析环境变量；无默认值的可选项按TOML字面量推断，失败时视为字符串
+    fn env_value(&self, key: &str, raw: &str) -> Value {
+        match get_path(&self.defaults, key) {
+            Some(Value::String(_)) => Value::String(raw.to_string()),
+            Some(Value::Array(_)) => Value::Array(
+                raw.split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(|s| Value::String(s.to_string()))
+                    .collect(),
+            ),
+            _ => parse_literal(raw).unwrap_or_else(|| Value::String(raw.to_string())),
+        }
+    }
+
+    /// 设置单个配置项
+    pub fn set(&mut self, key: &str, value: impl Into<Value>, source: ConfigSource) {
+        set_path(&mut self.merged, key, value.into());
+        self.sources.insert(key.to_string(), source);
+    }
+
+    /// 清除可选配置项（如命令行指定的时间点覆盖配置文件中的另一种时间点）
+    pub fn unset(&mut self, key: &str, source: ConfigSource) {
+        remove_path(&mut self.merged, key);
+        self.sources.insert(key.to_string(), source);
+    }
+
+    /// 当前生效的配置项及其来源，按键排序
+    pub fn entries(&self) -> Vec<(String, Value, ConfigSource)> {
+        let mut keys = Vec::new();
+        collect_leaf_keys(&self.merged, "", &mut |key| keys.push(key));
+        keys.into_iter()
+            .filter_map(|key| {
+                let value = get_path(&self.merged, &key)?.clone();
+                let source = self.source(&key);
+                Some((key, value, source))
+            })
+            .collect()
+    }
+
+    fn source(&self, key: &str) -> ConfigSource {
+        self.sources.get(key).cloned().unwrap_or(ConfigSource::Default)
+    }
+
+    /// 反序列化为配置结构。类型错误的配置项会记录到校验器并回退为默认值，
+    /// 以便一次性报告所有问题
+    pub fn resolve(&self) -> Result<(Config, Validator)> {
+        let mut sources = self.sources.clone();
+        for name in &self.unknown_env {
+            sources.insert(name.clone(), ConfigSource::Env(name.clone()));
+        }
+        let mut validator = Validator::new(sources);
+        let mut merged = self.merged.clone();
+
+        // 被 `*_env` 配置项引用的变量（如 salt_env = "EL_MASK_SALT"）不是配置项
+        let mut referenced = Vec::new();
+        collect_leaf_keys(&merged, "", &mut |key| {
+            if key.ends_with("_env")
+                && let Some(Value::String(name)) = get_path(&merged, &key)
+            {
+                referenced.push(name.clone());
+            }
+        });
+        for name in self.unknown_env.iter().filter(|name| !referenced.contains(name)) {
+            validator.error(name, "environment variable does not match any configuration key");
+        }
+
+        loop {
+            match serde_path_to_error::deserialize::<_, Config>(merged.clone()) {
+                Ok(config) => return Ok((config, validator)),
+                Err(err) => {
+                    let key = err.path().to_string();
+
+                    // 环境变量按字面量推断的类型不匹配时，先按字符串重试
+                    if matches!(self.sources.get(&key), Some(ConfigSource::Env(_)))
+                        && let Some(value) = get_path(&merged, &key)
+                        && !value.is_str()
+                        && !value.is_table()
+                    {
+                        let text = value.to_string();
+                        set_path(&mut merged, &key, Value::String(text));
+                        continue;
+                    }
+
+                    // toml的错误信息会附带键路径，只保留第一行
+                    let message = err.inner().to_string();
+                    let message = message.lines().next().unwrap_or_default();
+                    validator.error(&key, unknown_key_message(message).unwrap_or_else(|| message.to_string()));
+
+                    // 回退为默认值后重试；无法回退时直接报告已收集的问题
+                    let fallback = get_path(&self.defaults, &key).cloned();
+                    let changed = match fallback {
+                        Some(value) if get_path(&merged, &key) != Some(&value) => {
+                            set_path(&mut merged, &key, value);
+                            true
+                        }
+                        Some(_) => false,
+                        None => remove_path(&mut merged, &key),
+                    };
+                    if !changed {
+                        validator.finish()?;
+                        anyhow::bail!("Invalid configuration: {}", err);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 在配置节中匹配环境变量的剩余部分：优先完整的键名，其次按子节前缀递归，
+/// 都不匹配时视为该节的键（无默认值的可选项），未知键在反序列化时报告
+fn nested_key(table: &Table, field: &str) -> String {
+    if table.get(field).is_some_and(|value| !value.is_table()) {
+        return field.to_string();
+    }
+    table
+        .iter()
+        .find_map(|(name, value)| {
+            let rest = field.strip_prefix(name.as_str())?.strip_prefix('_')?;
+            let child = value.as_table()?;
+            (!rest.is_empty()).then(|| format!("{}.{}", name, nested_key(child, rest)))
+        })
+        .unwrap_or_else(|| field.to_string())
+}
+
+/// 未知配置项（拼写错误等）的说明，提示最接近的有效键名
+fn unknown_key_message(message: &str) -> Option<String> {
+    let rest = message.strip_prefix("unknown field `")?;
+    let (name, rest) = rest.split_once('`')?;
+    let closest = rest
+        .split('`')
+        .skip(1)
+        .step_by(2)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .min()
+        .filter(|(distance, _)| *distance <= 2);
+    Some(match closest {
+        Some((_, candidate)) => format!("unknown key {}, did you mean {}?", name, candidate),
+        None => format!("unknown key {}", name),
+    })
+}
+
+/// 两个键名之间的编辑距离
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb { prev } else { 1 + prev.min(row[j]).min(current) };
+            prev = current;
+        }
+    }
+    row[b.len()]
+}
+
+/// 将单个TOML字面量（整数、浮点数、布尔值）解析为值
+fn parse_literal(raw: &str) -> Option<Value> {
+    let table: Table = format!("v = {}", raw).parse().ok()?;
+    match table.get("v")? {
+        v @ (Value::Integer(_) | Value::Float(_) | Value::Boolean(_)) => Some(v.clone()),
+        _ => None,
+    }
+}
+
+/// 遍历表中所有叶子节点（非表的值）的键路径
+fn collect_leaf_keys(table: &Table, prefix: &str, f: &mut dyn FnMut(String)) {
+    for (k, v) in table {
+        let key = if prefix.is_empty() {
+            k.clone()
+        } else {
+            format!("{}.{}", prefix, k)
+        };
+        match v {
+            Value::Table(t) => collect_leaf_keys(t, &key, f),
+            _ => f(key),
+        }
+    }
+}
+
+fn get_path<'a>(table: &'a Table, key: &str) -> Option<&'a Value> {
+    let mut parts = key.split('.');
+    let mut value = table.get(parts.next()?)?;
+    for part in parts {
+        value = value.as_table()?.get(part)?;
+    }
+    Some(value)
+}
+
+fn set_path(table: &mut Table, key: &str, value: Value) {
+    match key.split_once('.') {
+        Some((head, rest)) => {
+            let entry = table
+                .entry(head.to_string())
+                .or_insert_with(|| Value::Table(Table::new()));
+            if !entry.is_table() {
+                *entry = Value::Table(Table::new());
+            }
+            if let Value::Table(child) = entry {
+                set_path(child, rest, value);
+            }
+        }
+        None => {
+            table.insert(key.to_string(), value);
+        }
+    }
+}
+
+fn remove_path(table: &mut Table, key: &str) -> bool {
+    match key.split_once('.') {
+        Some((head, rest)) => match table.get_mut(head) {
+            Some(Value::Table(child)) => remove_path(child, rest),
+            _ => false,
+        },
+        None => table.remove(key).is_some(),
+    }
+}
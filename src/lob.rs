@@ -0,0 +1,164 @@
+use crate::config::{BlobMode, ExportConfig, ExportFormat};
+use crate::db::{BlobSpool, ColumnInfo, ColumnKind, Value};
+use crate::masking::Masker;
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 按 `[export.lob]` 将二进制值转换为Base64/十六进制文本，或写入附属文件并以文件路径代替
+pub struct LobWriter {
+    mode: BlobMode,
+    /// 附属文件的实际目录
+    dir: PathBuf,
+    /// 写入字段中的目录（与配置一致，相对于输出文件所在目录）
+    display_dir: String,
+    /// 文件名中使用的列名
+    names: Vec<String>,
+    kinds: Vec<ColumnKind>,
+    dir_created: bool,
+    active: bool,
+    files_written: u64,
+}
+
+impl LobWriter {
+    pub fn new(config: &ExportConfig, columns: &[ColumnInfo]) -> Self {
+        let base = Path::new(&config.output_file).parent().unwrap_or(Path::new(""));
+        Self {
+            mode: config.lob.blob,
+            dir: base.join(&config.lob.dir),
+            display_dir: config.lob.dir.trim_end_matches(['/', '\\']).to_string(),
+            names: columns.iter().map(|c| file_name_part(&c.name)).collect(),
+            kinds: columns.iter().map(|c| c.kind).collect(),
+            dir_created: false,
+            active: encodes_binary(config)
+                && columns.iter().any(|c| matches!(c.kind, ColumnKind::Binary | ColumnKind::Blob)),
+            files_written: 0,
+        }
+    }
+
+    /// 写入的附属文件数
+    pub fn files_written(&self) -> u64 {
+        self.files_written
+    }
+
+    /// `blob = "file"` 时BLOB列不读入内存，由数据库层直接写入附属文件目录下的临时文件；
+    /// `indices` 为各输出列在查询结果（共 `source_count` 列）中的位置，脱敏列仍按原始字节读取
+    pub fn blob_spool(&mut self, indices: &[usize], source_count: usize, masker: &Masker) -> Result<Option<BlobSpool>> {
+        if !self.active || self.mode != BlobMode::File {
+            return Ok(None);
+        }
+        let mut columns = vec![false; source_count];
+        for (i, &index) in indices.iter().enumerate() {
+            if self.kinds[i] == ColumnKind::Blob && !masker.is_masked(i) && index < source_count {
+                columns[index] = true;
+            }
+        }
+        if !columns.contains(&true) {
+            return Ok(None);
+        }
+        self.create_dir()?;
+        Ok(Some(BlobSpool {
+            dir: self.dir.clone(),
+            columns,
+        }))
+    }
+
+    /// 删除被过滤掉的行已写入的临时文件
+    pub fn discard(paths: Vec<PathBuf>) {
+        for path in paths {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    /// 行中已写入临时文件的BLOB
+    pub fn spooled_files(values: &[Value]) -> Vec<PathBuf> {
+        values
+            .iter()
+            .filter_map(|value| match value {
+                Value::BlobFile(path) => Some(path.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// `row` 为输出文件中的行号（从1开始），用于附属文件命名
+    pub fn apply(&mut self, row: u64, mut values: Vec<Value>) -> Result<Vec<Value>> {
+        if !self.active {
+            return Ok(values);
+        }
+        for (i, value) in values.iter_mut().enumerate() {
+            let text = match value {
+                Value::Binary(bytes) => match self.mode {
+                    BlobMode::Base64 => STANDARD.encode(bytes),
+                    BlobMode::Hex => value.to_string(),
+                    BlobMode::File => self.write_file(row, i, bytes)?,
+                },
+                Value::BlobFile(spooled) => self.rename_file(row, i, spooled)?,
+                _ => continue,
+            };
+            *value = Value::Text(text);
+        }
+        Ok(values)
+    }
+
+    fn create_dir(&mut self) -> Result<()> {
+        if !self.dir_created {
+            fs::create_dir_all(&self.dir)
+                .with_context(|| format!("Failed to create LOB directory: {}", self.dir.display()))?;
+            self.dir_created = true;
+        }
+        Ok(())
+    }
+
+    fn write_file(&mut self, row: u64, column: usize, bytes: &[u8]) -> Result<String> {
+        self.create_dir()?;
+        let file_name = format!("{}_{}.bin", row, self.names[column]);
+        let path = self.dir.join(&file_name);
+        fs::write(&path, bytes).with_context(|| format!("Failed to write LOB file: {}", path.display()))?;
+        self.files_written += 1;
+        Ok(self.display_path(file_name))
+    }
+
+    /// 将数据库层写入的临时文件按行号重命名
+    fn rename_file(&mut self, row: u64, column: usize, spooled: &Path) -> Result<String> {
+        let file_name = format!("{}_{}.bin", row, self.names[column]);
+        let path = self.dir.join(&file_name);
+        fs::rename(spooled, &path).with_context(|| format!("Failed to write LOB file: {}", path.display()))?;
+        self.files_written += 1;
+        Ok(self.display_path(file_name))
+    }
+
+    /// 写入字段中的附属文件路径
+    fn display_path(&self, file_name: String) -> String {
+        if self.display_dir.is_empty() {
+            file_name
+        } else {
+            format!("{}/{}", self.display_dir, file_name)
+        }
+    }
+}
+
+/// 二进制值是否转换为文本（Base64、十六进制或附属文件路径）；SQL、Arrow、Avro格式直接写入二进制，
+/// 仅在 `blob = "file"` 时转换
+pub fn encodes_binary(config: &ExportConfig) -> bool {
+    config.lob.blob == BlobMode::File
+        || !matches!(config.format, ExportFormat::Sql | ExportFormat::ArrowIpc | ExportFormat::Avro)
+}
+
+/// 长度为 `size` 字节的二进制值转换为文本后的长度，附属文件路径的长度不固定
+pub fn encoded_size(config: &ExportConfig, size: u32) -> Option<u32> {
+    match config.lob.blob {
+        BlobMode::Base64 => Some(size.div_ceil(3) * 4),
+        BlobMode::Hex => Some(size * 2),
+        BlobMode::File => None,
+    }
+}
+
+/// 列名中不适合用作文件名的字符替换为 `_`
+fn file_name_part(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '_' | '-' | '$' | '#') { c } else { '_' })
+        .collect()
+}
@@ -1,102 +1,46 @@
+mod arrow;
+mod avro;
+mod cli;
 mod config;
 mod db;
+mod delimited;
+mod encoding;
 mod export;
-
-use clap::{Parser, Subcommand};
-use config::{CompressionType, Config, DatabaseConfig, ExportConfig, ExportFormat, LoggingConfig};
-use db::oracle::OracleDatabase;
+mod expr;
+mod fixed;
+mod format;
+mod insert;
+mod layers;
+mod lob;
+mod manifest;
+mod mapping;
+mod masking;
+mod preview;
+mod schema;
+mod sink;
+mod sqlldr;
+mod table;
+mod transform;
+mod validate;
+mod writer;
+mod xlsx;
+
+use clap::Parser;
+use cli::{Cli, Commands, ConfigCommands, ConnectionArgs, ExportArgs};
+use config::{Config, ConfigSource, DatabaseConfig, LoggingConfig};
+use db::oracle::{ConnectFailure, OracleDatabase};
 use db::Database;
 use export::Exporter;
+use layers::ConfigLayers;
 use anyhow::Result;
 use std::fs;
 use std::path::Path;
-use tracing::info;
+use std::time::Instant;
+use tracing::{error, info};
 use tracing_subscriber::{fmt, EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
 
-#[derive(Parser)]
-#[command(name = "el")]
-#[command(about = "数据导出导入工具 - Data Export/Import Tool", long_about = None)]
-struct Cli {
-    /// 详细日志 (Verbose logging)
-    #[arg(short, long, global = true)]
-    verbose: bool,
-    
-    #[command(subcommand)]
-    command: Commands,
-}
-
-#[derive(Subcommand)]
-enum Commands {
-    /// 导出数据 (Export data)
-    Export {
-        /// 配置文件路径 (Config file path)
-        #[arg(short, long)]
-        config: Option<String>,
-
-        /// 数据库类型 (Database type: oracle/mysql/postgresql)
-        #[arg(long)]
-        db_type: Option<String>,
-
-        /// 数据库连接字符串 (Database connection string: host:port/service_name)
-        #[arg(long)]
-        conn: Option<String>,
-
-        /// 用户名 (Username)
-        #[arg(long)]
-        username: Option<String>,
-
-        /// 密码 (Password)
-        #[arg(long)]
-        password: Option<String>,
-
-        /// 查询SQL或SQL文件路径 (Query SQL or SQL file path)
-        #[arg(long)]
-        query: Option<String>,
-
-        /// 输出文件 (Output file)
-        #[arg(short, long)]
-        output: Option<String>,
-
-        /// 导出格式 (Export format: csv/tsv/custom)
-        #[arg(long, default_value = "csv")]
-        format: String,
-
-        /// 分隔符 (Delimiter)
-        #[arg(long)]
-        delimiter: Option<String>,
-
-        /// 显示进度 (Show progress)
-        #[arg(long, default_value = "false")]
-        progress: bool,
-
-        /// 批量获取大小 (Fetch size)
-        #[arg(long, default_value = "1000")]
-        fetch: usize,
-
-        /// 包含表头 (Include header)
-        #[arg(long, default_value = "false")]
-        header: bool,
-
-        /// 缓冲区大小（字节）(Buffer size in bytes)
-        #[arg(long, default_value = "1048576")]
-        buffer_size: usize,
-
-        /// 压缩类型 (Compression type: none/gzip)
-        #[arg(long, default_value = "none")]
-        compression: String,
-
-        /// 日志文件路径 (Log file path, append mode)
-        #[arg(long)]
-        log_file: Option<String>,
-
-        /// 进度输出间隔（行数）(Progress output interval in rows)
-        #[arg(long, default_value = "1000000")]
-        progress_interval: u64,
-    },
-}
-
-/// 初始化tracing日志系统
-fn init_tracing(log_file: Option<&String>, verbose: bool) -> Result<()> {
+/// 初始化tracing日志系统，`stderr` 为true时控制台日志写入标准错误（数据写入标准输出时）
+fn init_tracing(log_file: Option<&String>, verbose: bool, stderr: bool) -> Result<()> {
     let level = if verbose { "debug" } else { "info" };
     
     // 优先使用环境变量，如果没有设置则使用verbose参数
@@ -117,6 +61,11 @@ fn init_tracing(log_file: Option<&String>, verbose: bool) -> Result<()> {
             .with(env_filter)
             .with(fmt::layer().with_writer(file_appender).with_ansi(false))
             .init();
+    } else if stderr {
+        tracing_subscriber::registry()
+            .with(env_filter)
+            .with(fmt::layer().with_writer(std::io::stderr))
+            .init();
     } else {
         // 输出到控制台
         tracing_subscriber::registry()
@@ -128,6 +77,43 @@ fn init_tracing(log_file: Option<&String>, verbose: bool) -> Result<()> {
     Ok(())
 }
 
+/// 连接数据库并输出诊断信息，失败时按原因返回退出码
+fn test_connection(db_config: DatabaseConfig) -> i32 {
+    info!("Connecting to {} ({})...", db_config.connection_string, db_config.username);
+    let mut db = OracleDatabase::new(db_config);
+
+    let start = Instant::now();
+    if let Err(e) = db.connect() {
+        let failure = ConnectFailure::classify(&e);
+        error!("Connection failed ({:?}): {:#}", failure, e);
+        return failure.exit_code();
+    }
+    let connect_time = start.elapsed();
+
+    match db.diagnostics() {
+        Ok(diag) => {
+            info!("Connection successful!");
+            info!("  Connect time: {:.2} ms", connect_time.as_secs_f64() * 1000.0);
+            info!("  Round-trip latency: {:.2} ms", diag.round_trip.as_secs_f64() * 1000.0);
+            info!("  Server version: {}", diag.server_version);
+            for line in diag.server_banner.lines() {
+                info!("    {}", line);
+            }
+            info!("  Client version: {}", diag.client_version);
+            info!("  NLS character set: {}", diag.nls_characterset);
+            info!("  NLS NCHAR character set: {}", diag.nls_nchar_characterset);
+            info!("  Session time zone: {}", diag.session_time_zone);
+            info!("  Session user: {}", diag.session_user);
+            info!("  Current schema: {}", diag.current_schema);
+            0
+        }
+        Err(e) => {
+            error!("Connected, but failed to collect diagnostics: {:#}", e);
+            1
+        }
+    }
+}
+
 /// 读取SQL查询，支持直接传入SQL字符串或SQL文件路径
 fn read_query_or_file(input: &str) -> Result<String> {
     let path = Path::new(input);
@@ -143,151 +129,121 @@ fn read_query_or_file(input: &str) -> Result<String> {
     }
 }
 
+/// 按优先级合并各层配置：默认值 < 配置文件 < 环境变量 < 命令行参数
+fn build_layers(connection: &ConnectionArgs, export: Option<&ExportArgs>, verbose: bool) -> Result<ConfigLayers> {
+    let mut layers = ConfigLayers::new()?;
+    if let Some(ref path) = connection.config {
+        layers.merge_file(path)?;
+    }
+    layers.merge_env();
+
+    connection.apply(&mut layers);
+    if let Some(export) = export {
+        export.apply(&mut layers);
+    }
+    if verbose {
+        layers.set("logging.verbose", true, ConfigSource::Cli("--verbose"));
+    }
+
+    Ok(layers)
+}
+
+/// 合并并校验完整的导出配置
+fn resolve_export_config(connection: &ConnectionArgs, export: &ExportArgs, verbose: bool) -> Result<Config> {
+    let layers = build_layers(connection, Some(export), verbose)?;
+    let (mut config, mut validator) = layers.resolve()?;
+    // 处理query字段，支持SQL文件路径
+    config.export.query = read_query_or_file(&config.export.query)?;
+
+    validator.check_database(&config.database);
+    validator.check_export(&config.export);
+    validator.finish()?;
+    Ok(config)
+}
+
+/// 合并并校验数据库及日志配置
+fn resolve_connection_config(connection: &ConnectionArgs, verbose: bool) -> Result<(DatabaseConfig, LoggingConfig)> {
+    let layers = build_layers(connection, None, verbose)?;
+    let (config, mut validator) = layers.resolve()?;
+
+    validator.check_database(&config.database);
+    validator.finish()?;
+    Ok((config.database, config.logging))
+}
+
+/// `config show` 中以掩码显示的配置项
+const SECRET_KEYS: &[&str] = &["database.password", "export.s3.secret_access_key", "export.masking.salt"];
+
+/// 输出生效的配置及来源，密码及密钥以掩码显示
+fn show_config(layers: &ConfigLayers) -> Result<()> {
+    let entries = layers.entries();
+    let lines: Vec<(String, String, String, ConfigSource)> = entries
+        .into_iter()
+        .map(|(key, value, source)| {
+            let (section, name) = key.split_once('.').unwrap_or(("", key.as_str()));
+            let value = match value {
+                _ if SECRET_KEYS.contains(&key.as_str()) => "\"******\"".to_string(),
+                // 控制字符（如记录结束符中的换行）按转义序列显示，保持单行
+                toml::Value::String(s) => serde_json::to_string(&s).unwrap_or_default(),
+                other => other.to_string(),
+            };
+            (section.to_string(), name.to_string(), value, source)
+        })
+        .collect();
+
+    let width = lines
+        .iter()
+        .map(|(_, name, value, _)| name.len() + value.len() + 3)
+        .max()
+        .unwrap_or(0);
+
+    let mut current_section = None;
+    for (section, name, value, source) in &lines {
+        if current_section != Some(section) {
+            if current_section.is_some() {
+                println!();
+            }
+            println!("[{}]", section);
+            current_section = Some(section);
+        }
+        let assignment = format!("{} = {}", name, value);
+        println!("{:<width$}  # {}", assignment, source, width = width);
+    }
+
+    println!();
+    let (mut config, mut validator) = layers.resolve()?;
+    config.export.query = read_query_or_file(&config.export.query)?;
+    validator.check_database(&config.database);
+    validator.check_export(&config.export);
+    validator.finish()?;
+    println!("Configuration is valid.");
+    Ok(())
+}
+
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
     match cli.command {
         Commands::Export {
-            config,
-            db_type,
-            conn,
-            username,
-            password,
-            query,
-            output,
-            format,
-            delimiter,
-            progress,
-            fetch,
-            header,
-            buffer_size,
-            compression,
-            log_file,
-            progress_interval,
+            connection,
+            export,
+            dry_run,
         } => {
-            let (db_config, export_config, logging_config) = if let Some(ref config_path) = config {
-                // 从配置文件加载
-                let cfg = Config::from_file(config_path)?;
-                let mut exp_cfg = cfg.export;
-                // 处理配置文件中的query字段，支持SQL文件路径
-                exp_cfg.query = read_query_or_file(&exp_cfg.query)?;
-                
-                // 命令行参数优先级高于配置文件
-                let mut db_cfg = cfg.database;
-                let mut log_cfg = cfg.logging;
-                
-                // 覆盖数据库配置
-                if let Some(ref dt) = db_type {
-                    db_cfg.db_type = dt.clone();
-                }
-                if let Some(ref c) = conn {
-                    db_cfg.connection_string = c.clone();
-                }
-                if let Some(ref u) = username {
-                    db_cfg.username = u.clone();
-                }
-                if let Some(ref p) = password {
-                    db_cfg.password = p.clone();
-                }
-                if fetch != 1000 {  // 如果不是默认值，则覆盖
-                    db_cfg.fetch_size = fetch;
-                }
-                
-                // 覆盖导出配置
-                if let Some(ref q) = query {
-                    exp_cfg.query = read_query_or_file(q)?;
-                }
-                if let Some(ref o) = output {
-                    exp_cfg.output_file = o.clone();
-                }
-                if format != "csv" {  // 如果不是默认值，则覆盖
-                    exp_cfg.format = match format.to_lowercase().as_str() {
-                        "csv" => ExportFormat::Csv,
-                        "tsv" => ExportFormat::Tsv,
-                        "custom" => ExportFormat::Custom,
-                        _ => exp_cfg.format,
-                    };
-                }
-                if let Some(ref d) = delimiter {
-                    exp_cfg.delimiter = d.clone();
-                }
-                if progress {  // 如果命令行指定了progress，则覆盖
-                    exp_cfg.show_progress = true;
-                }
-                if header {  // 如果命令行指定了header，则覆盖
-                    exp_cfg.include_header = true;
-                }
-                if buffer_size != 1048576 {  // 如果不是默认值，则覆盖
-                    exp_cfg.buffer_size = buffer_size;
-                }
-                if compression != "none" {  // 如果不是默认值，则覆盖
-                    exp_cfg.compression = match compression.to_lowercase().as_str() {
-                        "gzip" => CompressionType::Gzip,
-                        _ => exp_cfg.compression,
-                    };
-                }
-                if progress_interval != 1000000 {  // 如果不是默认值，则覆盖
-                    exp_cfg.progress_interval = progress_interval;
-                }
-                
-                // 覆盖日志配置
-                if log_file.is_some() {
-                    log_cfg.log_file = log_file;
-                }
-                if cli.verbose {
-                    log_cfg.verbose = true;
-                }
-                
-                (db_cfg, exp_cfg, log_cfg)
-            } else {
-                // 从命令行参数构建配置
-                let db_config = DatabaseConfig {
-                    db_type: db_type.unwrap_or_else(|| "oracle".to_string()),
-                    connection_string: conn.ok_or_else(|| anyhow::anyhow!("Connection string is required"))?,
-                    username: username.ok_or_else(|| anyhow::anyhow!("Username is required"))?,
-                    password: password.ok_or_else(|| anyhow::anyhow!("Password is required"))?,
-                    fetch_size: fetch,
-                };
-
-                let export_format = match format.to_lowercase().as_str() {
-                    "csv" => ExportFormat::Csv,
-                    "tsv" => ExportFormat::Tsv,
-                    "custom" => ExportFormat::Custom,
-                    _ => ExportFormat::Csv,
-                };
-
-                let query_input = query.ok_or_else(|| anyhow::anyhow!("Query is required"))?;
-                let query_sql = read_query_or_file(&query_input)?;
-                
-                let compression_type = match compression.to_lowercase().as_str() {
-                    "gzip" => CompressionType::Gzip,
-                    _ => CompressionType::None,
-                };
-                
-                let export_config = ExportConfig {
-                    query: query_sql,
-                    output_file: output.ok_or_else(|| anyhow::anyhow!("Output file is required"))?,
-                    format: export_format,
-                    delimiter: delimiter.unwrap_or_else(|| "\x03".to_string()),
-                    show_progress: progress,
-                    include_header: header,
-                    buffer_size,
-                    compression: compression_type,
-                    progress_interval,
-                };
-
-                let logging_config = LoggingConfig {
-                    log_file,
-                    verbose: cli.verbose,
-                };
-
-                (db_config, export_config, logging_config)
-            };
+            // 合并配置：默认值 < 配置文件 < 环境变量 < 命令行参数
+            let Config {
+                database: db_config,
+                export: export_config,
+                logging: logging_config,
+            } = resolve_export_config(&connection, &export, cli.verbose)?;
 
             // 初始化tracing
-            init_tracing(logging_config.log_file.as_ref(), logging_config.verbose)?;
+            init_tracing(
+                logging_config.log_file.as_ref(),
+                logging_config.verbose,
+                export_config.writes_to_stdout(),
+            )?;
             
-            if let Some(ref config_path) = config {
+            if let Some(ref config_path) = connection.config {
                 info!("Loading configuration from: {}", config_path);
             }
 
@@ -300,14 +256,24 @@ fn main() -> Result<()> {
             tracing::debug!("  Output file: {}", export_config.output_file);
             tracing::debug!("  Format: {:?}", export_config.format);
             tracing::debug!("  Delimiter: {:?}", export_config.delimiter);
+            tracing::debug!("  Record terminator: {:?}", export_config.record_terminator);
+            tracing::debug!("  Quote style: {:?}", export_config.quote_style);
+            tracing::debug!("  Sanitize: {:?}", export_config.sanitize);
+            tracing::debug!("  Encoding: {} (unmappable: {:?}, BOM: {})", export_config.encoding, export_config.unmappable, export_config.bom);
             tracing::debug!("  Show progress: {}", export_config.show_progress);
             tracing::debug!("  Include header: {}", export_config.include_header);
             tracing::debug!("  Buffer size: {} bytes", export_config.buffer_size);
             tracing::debug!("  Compression: {:?}", export_config.compression);
+            tracing::debug!("  Snapshot: {:?}", export_config.snapshot_point());
+            tracing::debug!("  Schema output: {:?}", export_config.schema_output);
+            tracing::debug!("  LOB: blob={:?}, max_length={:?}", export_config.lob.blob, export_config.lob.max_length);
+            if !export_config.masking.columns.is_empty() {
+                tracing::debug!("  Masking: {:?}", export_config.masking.columns.keys().collect::<Vec<_>>());
+            }
             
-            // 输出SQL脚本内容（verbose模式）
-            tracing::debug!("Query SQL:");
-            tracing::debug!("{}", export_config.query);
+            if !export_config.tables.is_empty() {
+                tracing::debug!("  Tables: {:?}", export_config.tables);
+            }
 
             // 执行导出
             info!("Connecting to {} database...", db_config.db_type);
@@ -315,14 +281,83 @@ fn main() -> Result<()> {
             db.connect()?;
             info!("Connected successfully!");
 
-            info!("Starting export...");
-            let mut exporter = Exporter::new(export_config);
-            let stats = exporter.export(&mut db)?;
+            if let Some(point) = export_config.snapshot_point() {
+                if dry_run {
+                    // 试运行需要写入plan_table，闪回模式下不允许DML，因此只解析SCN
+                    let scn = db.resolve_snapshot_scn(&point)?;
+                    info!("Consistent snapshot would use SCN {}", scn);
+                } else {
+                    let scn = db.begin_snapshot(&point)?;
+                    info!("Consistent snapshot enabled at SCN {}", scn);
+                }
+            }
+
+            // 表模式下每张表生成一个导出任务
+            let jobs = if export_config.tables.is_empty() {
+                vec![export_config]
+            } else {
+                let jobs = table::plan_exports(&mut db, &export_config)?;
+                info!("Resolved {} table(s) to export", jobs.len());
+                jobs
+            };
+
+            if dry_run {
+                for job in jobs {
+                    info!("Dry run for {}...", job.output_file);
+                    tracing::debug!("Query SQL:");
+                    tracing::debug!("{}", job.query);
+                    let exporter = Exporter::new(job);
+                    exporter.dry_run(&mut db)?.print_summary();
+                }
+                info!("Dry run completed, no data exported.");
+                return Ok(());
+            }
 
-            stats.print_summary();
+            for job in jobs {
+                info!("Starting export to {}...", job.output_file);
+                // 输出SQL脚本内容（verbose模式）
+                tracing::debug!("Query SQL:");
+                tracing::debug!("{}", job.query);
+                let mut exporter = Exporter::new(job);
+                let stats = exporter.export(&mut db)?;
+                stats.print_summary();
+            }
             info!("Export completed successfully!");
 
             Ok(())
         }
+        Commands::Query {
+            connection,
+            sql,
+            limit,
+            max_width,
+        } => {
+            let (db_config, logging_config) = resolve_connection_config(&connection, cli.verbose)?;
+            init_tracing(None, logging_config.verbose, false)?;
+
+            let query = read_query_or_file(&sql)?;
+            tracing::debug!("Query SQL:");
+            tracing::debug!("{}", query);
+
+            let mut db = OracleDatabase::new(db_config);
+            db.connect()?;
+
+            let result = db.execute_query(&query, Some(limit))?;
+            preview::print_table(&result, max_width);
+
+            Ok(())
+        }
+        Commands::TestConnection { connection } => {
+            let (db_config, logging_config) = resolve_connection_config(&connection, cli.verbose)?;
+            init_tracing(None, logging_config.verbose, false)?;
+
+            std::process::exit(test_connection(db_config));
+        }
+        Commands::Config {
+            command: ConfigCommands::Show { connection, export },
+        } => {
+            let layers = build_layers(&connection, Some(&export), cli.verbose)?;
+            show_config(&layers)
+        }
     }
 }
@@ -0,0 +1,82 @@
+use crate::config::{CompressionType, ExportConfig, ExportFormat, MaskRule};
+use crate::db::ColumnInfo;
+use crate::schema;
+use crate::sink;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// 导出清单，记录输出文件的来源、列、过滤条件及脱敏规则，供审计和下游校验
+#[derive(Serialize)]
+struct Manifest<'a> {
+    output_file: &'a str,
+    exported_at: String,
+    format: &'a ExportFormat,
+    encoding: &'a str,
+    compression: &'a CompressionType,
+    rows: u64,
+    source_table: Option<String>,
+    snapshot_scn: Option<u64>,
+    columns: &'a [ColumnInfo],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    filter: Option<&'a str>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    computed: &'a BTreeMap<String, String>,
+    masking: Vec<MaskEntry<'a>>,
+    /// 盐值来源（不记录盐值本身）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    masking_salt_env: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct MaskEntry<'a> {
+    column: &'a str,
+    #[serde(flatten)]
+    rule: &'a MaskRule,
+}
+
+/// 是否需要写入清单：显式开启或配置了脱敏规则
+pub fn enabled(config: &ExportConfig) -> bool {
+    config.manifest || !config.masking.columns.is_empty()
+}
+
+pub fn manifest_path(config: &ExportConfig) -> String {
+    schema::sidecar_path(&config.output_file, "manifest.json")
+}
+
+/// 写入清单文件，返回文件路径；`columns` 为映射后的输出列
+pub fn write_manifest(
+    config: &ExportConfig,
+    columns: &[ColumnInfo],
+    rows: u64,
+    snapshot_scn: Option<u64>,
+) -> Result<String> {
+    let manifest = Manifest {
+        output_file: &config.output_file,
+        exported_at: chrono::Local::now().to_rfc3339(),
+        format: &config.format,
+        encoding: &config.encoding,
+        compression: &config.compression,
+        rows,
+        source_table: config
+            .source_table
+            .as_ref()
+            .map(|t| format!("{}.{}", t.owner, t.name)),
+        snapshot_scn,
+        columns,
+        filter: config.filter.as_deref(),
+        computed: &config.computed,
+        masking: config
+            .masking
+            .columns
+            .iter()
+            .map(|(column, rule)| MaskEntry { column, rule })
+            .collect(),
+        masking_salt_env: config.masking.salt_env.as_deref(),
+    };
+
+    let path = manifest_path(config);
+    sink::write_file(config, &path, (serde_json::to_string_pretty(&manifest)? + "\n").as_bytes())
+        .with_context(|| format!("Failed to write manifest file: {}", path))?;
+    Ok(path)
+}
@@ -0,0 +1,122 @@
+use crate::config::ColumnMapping;
+use crate::db::{ColumnInfo, Value};
+use anyhow::{bail, Result};
+
+/// 按 `[export.mapping]` 选择、排序并重命名输出列
+pub struct ColumnMapper {
+    /// 输出列在查询结果中的位置
+    indices: Vec<usize>,
+    /// 输出列的表头名称
+    headers: Vec<String>,
+    /// 输出列与查询结果完全一致，无需重排
+    identity: bool,
+}
+
+impl ColumnMapper {
+    pub fn new(mapping: &ColumnMapping, columns: &[ColumnInfo]) -> Result<Self> {
+        let find = |name: &str, list: &str| -> Result<usize> {
+            match columns.iter().position(|c| c.name.eq_ignore_ascii_case(name)) {
+                Some(index) => Ok(index),
+                None => bail!(
+                    "Column {} in mapping {} not found in query result (columns: {})",
+                    name,
+                    list,
+                    columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>().join(", ")
+                ),
+            }
+        };
+
+        // include为空时选择全部列，再去掉exclude中的列
+        let mut selected: Vec<usize> = if mapping.include.is_empty() {
+            (0..columns.len()).collect()
+        } else {
+            mapping
+                .include
+                .iter()
+                .map(|name| find(name, "include"))
+                .collect::<Result<_>>()?
+        };
+        selected.sort_unstable();
+        selected.dedup();
+        for name in &mapping.exclude {
+            let index = find(name, "exclude")?;
+            selected.retain(|&i| i != index);
+        }
+
+        // order中的列排在最前，其余列保持查询结果中的顺序
+        let mut indices = Vec::with_capacity(selected.len());
+        for name in &mapping.order {
+            let index = find(name, "order")?;
+            if !selected.contains(&index) {
+                bail!("Column {} in mapping order is not selected for output", name);
+            }
+            if indices.contains(&index) {
+                bail!("Column {} appears more than once in mapping order", name);
+            }
+            indices.push(index);
+        }
+        let rest: Vec<usize> = selected.into_iter().filter(|i| !indices.contains(i)).collect();
+        indices.extend(rest);
+
+        let mut headers = Vec::with_capacity(indices.len());
+        for &index in &indices {
+            headers.push(columns[index].name.clone());
+        }
+        for (name, output) in &mapping.rename {
+            let index = find(name, "rename")?;
+            match indices.iter().position(|&i| i == index) {
+                Some(pos) => headers[pos] = output.clone(),
+                None => bail!("Column {} in mapping rename is not selected for output", name),
+            }
+        }
+        for (i, header) in headers.iter().enumerate() {
+            if headers[..i].iter().any(|h| h.eq_ignore_ascii_case(header)) {
+                bail!("Duplicate output column name {} after mapping", header);
+            }
+        }
+
+        let identity = indices.len() == columns.len() && indices.iter().enumerate().all(|(i, &index)| i == index);
+        Ok(Self {
+            indices,
+            headers,
+            identity,
+        })
+    }
+
+    /// 输出列的表头名称
+    pub fn headers(&self) -> &[String] {
+        &self.headers
+    }
+
+    /// 各输出列在查询结果中的位置
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    /// 输出列的元数据，保留源列名（格式化规则按源列名匹配）
+    pub fn source_columns(&self, columns: &[ColumnInfo]) -> Vec<ColumnInfo> {
+        self.indices.iter().map(|&i| columns[i].clone()).collect()
+    }
+
+    /// 输出列的元数据，使用重命名后的列名
+    pub fn output_columns(&self, columns: &[ColumnInfo]) -> Vec<ColumnInfo> {
+        self.indices
+            .iter()
+            .zip(&self.headers)
+            .map(|(&i, header)| ColumnInfo {
+                name: header.clone(),
+                ..columns[i].clone()
+            })
+            .collect()
+    }
+
+    pub fn apply(&self, mut values: Vec<Value>) -> Vec<Value> {
+        if self.identity {
+            return values;
+        }
+        self.indices
+            .iter()
+            .map(|&i| std::mem::replace(&mut values[i], Value::Null))
+            .collect()
+    }
+}
@@ -0,0 +1,227 @@
+use crate::config::{MaskMethod, MaskRule, MaskingConfig};
+use crate::db::{ColumnInfo, ColumnKind, Value};
+use anyhow::{bail, Context, Result};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 默认的令牌前缀及长度（十六进制字符数）
+const DEFAULT_TOKEN_PREFIX: &str = "TKN_";
+const DEFAULT_TOKEN_LENGTH: usize = 16;
+
+/// 需要盐值的脱敏方式
+pub fn needs_salt(method: MaskMethod) -> bool {
+    matches!(method, MaskMethod::Sha256 | MaskMethod::Hmac | MaskMethod::Fake | MaskMethod::Token)
+}
+
+/// 读取盐值：`salt_env` 指定的环境变量优先于配置中的 `salt`
+pub fn resolve_salt(config: &MaskingConfig) -> Result<Option<String>> {
+    if let Some(ref var) = config.salt_env {
+        let salt = std::env::var(var).with_context(|| format!("Masking salt environment variable {} is not set", var))?;
+        return Ok(Some(salt));
+    }
+    Ok(config.salt.clone())
+}
+
+/// 按 `[export.masking]` 对输出列进行脱敏，在格式化之前应用
+pub struct Masker {
+    rules: Vec<Option<MaskRule>>,
+    kinds: Vec<ColumnKind>,
+    salt: Vec<u8>,
+    active: bool,
+}
+
+impl Masker {
+    pub fn new(config: &MaskingConfig, columns: &[ColumnInfo]) -> Result<Self> {
+        for name in config.columns.keys() {
+            if !columns.iter().any(|c| c.name.eq_ignore_ascii_case(name)) {
+                bail!(
+                    "Column {} in [export.masking.columns] not found in output columns (columns: {})",
+                    name,
+                    columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>().join(", ")
+                );
+            }
+        }
+
+        let rules: Vec<Option<MaskRule>> = columns
+            .iter()
+            .map(|column| {
+                config
+                    .columns
+                    .iter()
+                    .find(|(name, _)| column.name.eq_ignore_ascii_case(name))
+                    .map(|(_, rule)| rule.clone())
+            })
+            .collect();
+
+        let salt = resolve_salt(config)?;
+        if salt.is_none() && rules.iter().flatten().any(|rule| needs_salt(rule.method)) {
+            bail!("Masking salt is required for sha256/hmac/fake/token (set salt or salt_env)");
+        }
+
+        Ok(Self {
+            active: rules.iter().any(Option::is_some),
+            rules,
+            kinds: columns.iter().map(|c| c.kind).collect(),
+            salt: salt.unwrap_or_default().into_bytes(),
+        })
+    }
+
+    pub fn is_masked(&self, index: usize) -> bool {
+        matches!(self.rules.get(index), Some(Some(_)))
+    }
+
+    /// 第 `index` 列脱敏后是否可能不再是原类型（置NULL，以及数字、文本列的伪造值保持类型）
+    pub fn changes_type(&self, index: usize) -> bool {
+        match self.rules.get(index) {
+            Some(Some(rule)) => match rule.method {
+                MaskMethod::Null => false,
+                MaskMethod::Fake => !(self.kinds[index].is_numeric() || self.kinds[index] == ColumnKind::Text),
+                _ => true,
+            },
+            _ => false,
+        }
+    }
+
+    pub fn apply(&self, values: Vec<Value>) -> Vec<Value> {
+        if !self.active {
+            return values;
+        }
+        values
+            .into_iter()
+            .zip(&self.rules)
+            .map(|(value, rule)| match rule {
+                Some(rule) if value != Value::Null => self.mask(value, rule),
+                _ => value,
+            })
+            .collect()
+    }
+
+    fn mask(&self, value: Value, rule: &MaskRule) -> Value {
+        match rule.method {
+            MaskMethod::Null => Value::Null,
+            MaskMethod::Sha256 => {
+                let mut hasher = Sha256::new();
+                hasher.update(&self.salt);
+                hasher.update(value.into_string().as_bytes());
+                Value::Text(to_hex(&hasher.finalize()))
+            }
+            MaskMethod::Hmac => Value::Text(to_hex(&self.hmac(b"", value.into_string().as_bytes()))),
+            MaskMethod::Token => {
+                let digest = to_hex(&self.hmac(b"token:", value.into_string().as_bytes()));
+                let length = rule.length.unwrap_or(DEFAULT_TOKEN_LENGTH).min(digest.len());
+                let prefix = rule.prefix.as_deref().unwrap_or(DEFAULT_TOKEN_PREFIX);
+                Value::Text(format!("{}{}", prefix, &digest[..length]))
+            }
+            MaskMethod::Partial => Value::Text(partial_mask(&value.into_string(), rule)),
+            MaskMethod::Fake => match value {
+                Value::Number(text) => Value::Number(self.fake(&text, true)),
+                other => Value::Text(self.fake(&other.into_string(), false)),
+            },
+        }
+    }
+
+    fn hmac(&self, domain: &[u8], data: &[u8]) -> Vec<u8> {
+        // HMAC接受任意长度的密钥，new_from_slice不会失败
+        let mut mac = HmacSha256::new_from_slice(&self.salt).expect("HMAC accepts keys of any length");
+        mac.update(domain);
+        mac.update(data);
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    /// 保留格式的伪造值：数字、字母、汉字替换为同类字符，其余字符保持不变。
+    /// 随机数由盐值和原值决定，相同的输入总是得到相同的输出。
+    fn fake(&self, text: &str, numeric: bool) -> String {
+        let mut stream = FakeStream::new(self.hmac(b"fake:", text.as_bytes()));
+        let mut first_digit = true;
+        text.chars()
+            .map(|ch| {
+                let r = stream.next();
+                match ch {
+                    '0'..='9' => {
+                        // 数字的首位保持非零，避免改变位数
+                        let keep_nonzero = numeric && first_digit && ch != '0';
+                        first_digit = false;
+                        if keep_nonzero {
+                            char::from(b'1' + (r % 9) as u8)
+                        } else {
+                            char::from(b'0' + (r % 10) as u8)
+                        }
+                    }
+                    'a'..='z' => char::from(b'a' + (r % 26) as u8),
+                    'A'..='Z' => char::from(b'A' + (r % 26) as u8),
+                    // 常用汉字区间
+                    '\u{4E00}'..='\u{9FA5}' => char::from_u32(0x4E00 + r % (0x9FA5 - 0x4E00 + 1)).unwrap_or(ch),
+                    _ => ch,
+                }
+            })
+            .collect()
+    }
+}
+
+/// 部分遮盖：保留首尾指定数量的字符，其余替换为遮盖字符；
+/// 值过短时全部遮盖，避免泄露完整原值
+fn partial_mask(text: &str, rule: &MaskRule) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let keep_start = rule.keep_start.unwrap_or(0);
+    let keep_end = rule.keep_end.unwrap_or(0);
+    let mask_char = rule.mask_char.as_deref().and_then(|s| s.chars().next()).unwrap_or('*');
+
+    if keep_start + keep_end >= chars.len() {
+        return std::iter::repeat_n(mask_char, chars.len()).collect();
+    }
+    chars
+        .iter()
+        .enumerate()
+        .map(|(i, &ch)| {
+            if i < keep_start || i >= chars.len() - keep_end {
+                ch
+            } else {
+                mask_char
+            }
+        })
+        .collect()
+}
+
+/// 由种子扩展出的确定性随机数序列
+struct FakeStream {
+    seed: Vec<u8>,
+    block: Vec<u8>,
+    counter: u32,
+    pos: usize,
+}
+
+impl FakeStream {
+    fn new(seed: Vec<u8>) -> Self {
+        Self {
+            seed,
+            block: Vec::new(),
+            counter: 0,
+            pos: 0,
+        }
+    }
+
+    fn next(&mut self) -> u32 {
+        if self.pos + 4 > self.block.len() {
+            let mut hasher = Sha256::new();
+            hasher.update(&self.seed);
+            hasher.update(self.counter.to_le_bytes());
+            self.block = hasher.finalize().to_vec();
+            self.counter += 1;
+            self.pos = 0;
+        }
+        let bytes = [
+            self.block[self.pos],
+            self.block[self.pos + 1],
+            self.block[self.pos + 2],
+            self.block[self.pos + 3],
+        ];
+        self.pos += 4;
+        u32::from_le_bytes(bytes)
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,80 @@
+use crate::db::QueryResult;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// 将单元格内容处理为单行，并按显示宽度截断（中文字符按两个宽度计算）
+fn display_cell(value: &str, max_width: usize) -> String {
+    let single_line = value.replace("\r\n", "\\n").replace(['\n', '\r'], "\\n").replace('\t', " ");
+    if single_line.width() <= max_width {
+        return single_line;
+    }
+
+    let mut out = String::new();
+    let mut width = 0;
+    for ch in single_line.chars() {
+        let w = ch.width().unwrap_or(0);
+        if width + w + 1 > max_width {
+            break;
+        }
+        out.push(ch);
+        width += w;
+    }
+    out.push('…');
+    out
+}
+
+fn pad(value: &str, width: usize) -> String {
+    let padding = width.saturating_sub(value.width());
+    format!("{}{}", value, " ".repeat(padding))
+}
+
+fn separator(widths: &[usize]) -> String {
+    let parts: Vec<String> = widths.iter().map(|w| "-".repeat(w + 2)).collect();
+    format!("+{}+", parts.join("+"))
+}
+
+fn line(cells: &[String], widths: &[usize]) -> String {
+    let parts: Vec<String> = cells
+        .iter()
+        .zip(widths)
+        .map(|(cell, w)| format!(" {} ", pad(cell, *w)))
+        .collect();
+    format!("|{}|", parts.join("|"))
+}
+
+/// 以对齐表格形式输出查询结果，表头包含列名和列类型
+pub fn print_table(result: &QueryResult, max_width: usize) {
+    let names: Vec<String> = result.columns.iter().map(|c| display_cell(&c.name, max_width)).collect();
+    let types: Vec<String> = result.columns.iter().map(|c| display_cell(&c.type_name, max_width)).collect();
+    let rows: Vec<Vec<String>> = result
+        .rows
+        .iter()
+        .map(|row| row.iter().map(|v| display_cell(v, max_width)).collect())
+        .collect();
+
+    let mut widths: Vec<usize> = names
+        .iter()
+        .zip(&types)
+        .map(|(n, t)| n.width().max(t.width()))
+        .collect();
+    for row in &rows {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(cell.width());
+        }
+    }
+
+    let sep = separator(&widths);
+    println!("{}", sep);
+    println!("{}", line(&names, &widths));
+    println!("{}", line(&types, &widths));
+    println!("{}", sep);
+    for row in &rows {
+        println!("{}", line(row, &widths));
+    }
+    println!("{}", sep);
+
+    if result.has_more {
+        println!("{} rows shown (more rows available)", rows.len());
+    } else {
+        println!("{} rows", rows.len());
+    }
+}
@@ -0,0 +1,131 @@
+use crate::config::{BlobMode, ExportConfig, SchemaOutput, STDOUT_FILE};
+use crate::db::oracle::OracleDatabase;
+use crate::db::{ColumnInfo, ColumnKind};
+use crate::sink;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::Path;
+
+/// 可移植的JSON表结构描述，供导入端自动建表
+#[derive(Serialize)]
+struct SchemaDocument<'a> {
+    source: &'a str,
+    table: Option<String>,
+    columns: Vec<ColumnInfo>,
+}
+
+/// 根据输出文件计算附属文件路径，去掉压缩及格式扩展名后追加后缀
+/// 例如 `EMP.csv.gz` + `schema.json` => `EMP.schema.json`；写入标准输出时为当前目录下的 `stdout.schema.json`
+pub fn sidecar_path(output_file: &str, suffix: &str) -> String {
+    if output_file == STDOUT_FILE {
+        return format!("stdout.{}", suffix);
+    }
+    let path = Path::new(output_file);
+    let mut stem = path.to_path_buf();
+    if stem.extension().is_some_and(|ext| ext == "gz") {
+        stem.set_extension("");
+    }
+    stem.set_extension("");
+    format!("{}.{}", stem.to_string_lossy(), suffix)
+}
+
+/// 写入表结构附属文件，返回文件路径。`columns` 为输出列，`value_columns` 为
+/// [`writer::value_columns`](crate::writer::value_columns) 返回的列；DDL描述的是源表，不随列选择、映射及脱敏变化
+pub fn write_schema_file(
+    db: &mut OracleDatabase,
+    config: &ExportConfig,
+    columns: &[ColumnInfo],
+    value_columns: &[ColumnInfo],
+) -> Result<Option<String>> {
+    let (path, content) = match config.schema_output {
+        SchemaOutput::None => return Ok(None),
+        SchemaOutput::Json => {
+            let document = SchemaDocument {
+                source: "oracle",
+                table: config
+                    .source_table
+                    .as_ref()
+                    .map(|t| format!("{}.{}", t.owner, t.name)),
+                columns: output_columns(config, columns, value_columns),
+            };
+            let path = sidecar_path(&config.output_file, "schema.json");
+            (path, serde_json::to_string_pretty(&document)? + "\n")
+        }
+        SchemaOutput::Ddl => {
+            let table = config
+                .source_table
+                .as_ref()
+                .context("DDL schema output requires table mode (--table)")?;
+            let path = sidecar_path(&config.output_file, "ddl.sql");
+            (path, format!("{};\n", db.table_ddl(table)?))
+        }
+    };
+
+    sink::write_file(config, &path, content.as_bytes())
+        .with_context(|| format!("Failed to write schema file: {}", path))?;
+    Ok(Some(path))
+}
+
+/// 按实际写出的值描述输出列：脱敏或编码后变为文本的列描述为字符类型，
+/// 编码后的BLOB为CLOB（`blob = "file"` 时为附属文件路径）
+fn output_columns(config: &ExportConfig, columns: &[ColumnInfo], value_columns: &[ColumnInfo]) -> Vec<ColumnInfo> {
+    columns
+        .iter()
+        .zip(value_columns)
+        .map(|(column, value)| {
+            let mut value = value.clone();
+            if value.kind != column.kind {
+                value.type_name = match (column.kind, value.size) {
+                    (ColumnKind::Blob, _) if config.lob.blob != BlobMode::File => "CLOB".to_string(),
+                    (_, Some(size)) => format!("VARCHAR2({})", size),
+                    (_, None) => "VARCHAR2".to_string(),
+                };
+                value.precision = None;
+                value.scale = None;
+            }
+            value
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str, type_name: &str, kind: ColumnKind, size: Option<u32>) -> ColumnInfo {
+        ColumnInfo {
+            name: name.to_string(),
+            type_name: type_name.to_string(),
+            kind,
+            precision: Some(10),
+            scale: Some(0),
+            size,
+            nullable: false,
+        }
+    }
+
+    #[test]
+    fn describes_values_as_written() {
+        let config = ExportConfig::default();
+        let source = [
+            column("ID", "NUMBER(10)", ColumnKind::Integer, None),
+            column("SSN", "NUMBER(10)", ColumnKind::Integer, None),
+            column("PHOTO", "BLOB", ColumnKind::Blob, None),
+            column("TAG", "RAW(16)", ColumnKind::Binary, Some(16)),
+        ];
+        let mut values = source.to_vec();
+        values[1].kind = ColumnKind::Text;
+        values[1].nullable = true;
+        values[2].kind = ColumnKind::Text;
+        values[3].kind = ColumnKind::Text;
+        values[3].size = Some(24);
+
+        let described = output_columns(&config, &source, &values);
+        assert_eq!(described[0].type_name, "NUMBER(10)");
+        assert_eq!(described[1].type_name, "VARCHAR2");
+        assert_eq!(described[1].precision, None);
+        assert!(described[1].nullable);
+        assert_eq!(described[2].type_name, "CLOB");
+        assert_eq!(described[3].type_name, "VARCHAR2(24)");
+    }
+}
@@ -0,0 +1,216 @@
+mod s3;
+mod sftp;
+mod webhdfs;
+
+use crate::config::{ExportConfig, STDOUT_FILE};
+use anyhow::{bail, Context, Result};
+use std::fs::{self, File};
+use std::io::{self, Stdout, Write};
+use std::path::Path;
+use tracing::info;
+
+/// 导出数据的写入目标
+pub trait Sink: Write {
+    /// 写入全部数据后提交（如完成分段上传）；未提交的远程写入会被放弃
+    fn finish(self: Box<Self>) -> Result<()>;
+}
+
+/// 输出位置，按 `output_file` 的形式确定
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    Stdout,
+    /// 本地文件或命名管道
+    File(String),
+    /// `s3://bucket/key`
+    S3 { bucket: String, key: String },
+    /// `webhdfs://host:port/path`（`swebhdfs://` 使用HTTPS）
+    WebHdfs { https: bool, authority: String, path: String },
+    /// `sftp://[user@]host[:port]/path`
+    Sftp { user: Option<String>, host: String, port: u16, path: String },
+}
+
+/// WebHDFS的默认端口（NameNode HTTP）
+const WEBHDFS_PORT: u16 = 9870;
+/// SWebHDFS的默认端口（NameNode HTTPS）
+const SWEBHDFS_PORT: u16 = 9871;
+const SFTP_PORT: u16 = 22;
+
+impl Target {
+    pub fn parse(output_file: &str) -> Result<Self> {
+        if output_file == STDOUT_FILE {
+            return Ok(Target::Stdout);
+        }
+        let Some((scheme, rest)) = output_file.split_once("://") else {
+            return Ok(Target::File(output_file.to_string()));
+        };
+        let (authority, path) = match rest.find('/') {
+            Some(pos) => (&rest[..pos], &rest[pos..]),
+            None => (rest, ""),
+        };
+        if authority.is_empty() && scheme != "file" {
+            bail!("missing host or bucket in {}", output_file);
+        }
+        if path.len() <= 1 {
+            bail!("missing file path in {}", output_file);
+        }
+
+        match scheme.to_ascii_lowercase().as_str() {
+            "file" => Ok(Target::File(path.to_string())),
+            "s3" => Ok(Target::S3 {
+                bucket: authority.to_string(),
+                key: path[1..].to_string(),
+            }),
+            scheme @ ("webhdfs" | "swebhdfs") => {
+                let https = scheme == "swebhdfs";
+                let (host, port) = split_port(authority, if https { SWEBHDFS_PORT } else { WEBHDFS_PORT })?;
+                Ok(Target::WebHdfs {
+                    https,
+                    authority: format!("{}:{}", host, port),
+                    path: path.to_string(),
+                })
+            }
+            "sftp" => {
+                let (user, host) = match authority.rsplit_once('@') {
+                    Some((user, host)) => (Some(user.to_string()), host),
+                    None => (None, authority),
+                };
+                let (host, port) = split_port(host, SFTP_PORT)?;
+                Ok(Target::Sftp {
+                    user,
+                    host,
+                    port,
+                    path: path.to_string(),
+                })
+            }
+            other => bail!("unsupported output scheme {}:// (supported: s3, webhdfs, swebhdfs, sftp, file)", other),
+        }
+    }
+
+    /// 是否为本地文件或标准输出
+    pub fn is_local(&self) -> bool {
+        matches!(self, Target::Stdout | Target::File(_))
+    }
+}
+
+/// 拆分 `host[:port]`，IPv6地址需写在方括号中
+fn split_port(authority: &str, default_port: u16) -> Result<(String, u16)> {
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) if !port.contains(']') => (host, Some(port)),
+        _ => (authority, None),
+    };
+    let port = match port {
+        Some(port) => port.parse().with_context(|| format!("invalid port in {}", authority))?,
+        None => default_port,
+    };
+    Ok((host.trim_start_matches('[').trim_end_matches(']').to_string(), port))
+}
+
+/// 打开导出文件的写入目标
+pub fn open(config: &ExportConfig) -> Result<Box<dyn Sink>> {
+    open_target(config, &Target::parse(&config.output_file)?)
+}
+
+fn open_target(config: &ExportConfig, target: &Target) -> Result<Box<dyn Sink>> {
+    Ok(match target {
+        Target::Stdout => Box::new(StdoutSink(io::stdout())),
+        Target::File(path) => {
+            if is_fifo(path) {
+                info!("Waiting for a reader on named pipe {}...", path);
+            }
+            // 多表导出时输出目录可能尚不存在
+            if let Some(dir) = Path::new(path).parent()
+                && !dir.as_os_str().is_empty()
+            {
+                fs::create_dir_all(dir).with_context(|| format!("Failed to create output directory {}", dir.display()))?;
+            }
+            Box::new(FileSink(File::create(path).context("Failed to create output file")?))
+        }
+        Target::S3 { bucket, key } => Box::new(s3::S3Sink::new(&config.s3, bucket, key)?),
+        Target::WebHdfs { https, authority, path } => {
+            Box::new(webhdfs::WebHdfsSink::new(&config.webhdfs, *https, authority, path))
+        }
+        Target::Sftp { user, host, port, path } => {
+            Box::new(sftp::SftpSink::connect(&config.sftp, user.as_deref(), host, *port, path)?)
+        }
+    })
+}
+
+/// 写入附属文件（表结构、清单等），与导出文件位于同一位置（本地或远程）
+pub fn write_file(config: &ExportConfig, path: &str, content: &[u8]) -> Result<()> {
+    match Target::parse(path)? {
+        Target::File(path) => Ok(fs::write(path, content)?),
+        target => {
+            let mut sink = open_target(config, &target)?;
+            sink.write_all(content)?;
+            sink.finish()
+        }
+    }
+}
+
+struct StdoutSink(Stdout);
+
+impl Write for StdoutSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl Sink for StdoutSink {
+    fn finish(mut self: Box<Self>) -> Result<()> {
+        self.0.flush()?;
+        Ok(())
+    }
+}
+
+struct FileSink(File);
+
+impl Write for FileSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl Sink for FileSink {
+    fn finish(mut self: Box<Self>) -> Result<()> {
+        self.0.flush()?;
+        Ok(())
+    }
+}
+
+/// 输出路径是否为已存在的命名管道（打开时会等待读取端）
+#[cfg(unix)]
+fn is_fifo(path: &str) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    fs::metadata(path).is_ok_and(|m| m.file_type().is_fifo())
+}
+
+#[cfg(not(unix))]
+fn is_fifo(_path: &str) -> bool {
+    false
+}
+
+/// 按RFC 3986编码URI，只保留非保留字符，`keep_slash` 时保留路径分隔符
+fn uri_encode(text: &str, keep_slash: bool) -> String {
+    let mut out = String::with_capacity(text.len());
+    for b in text.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => out.push(b as char),
+            b'/' if keep_slash => out.push('/'),
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+/// 将写入错误包装为I/O错误
+fn io_error(error: anyhow::Error) -> io::Error {
+    io::Error::other(format!("{:#}", error))
+}
@@ -0,0 +1,280 @@
+use super::{io_error, uri_encode, Sink};
+use crate::config::S3Config;
+use anyhow::{anyhow, bail, Context, Result};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::io::{self, Write};
+use std::mem;
+use tracing::{debug, warn};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// S3分段上传的最大分段数
+const MAX_PARTS: usize = 10_000;
+
+/// 以分段上传写入S3兼容存储，数据不超过一个分段时使用单次PutObject
+pub struct S3Sink {
+    agent: ureq::Agent,
+    credentials: Credentials,
+    region: String,
+    /// `http(s)://host[:port]`
+    base: String,
+    /// 请求的Host头（与签名一致）
+    host: String,
+    /// 对象在服务上的路径（已编码），路径形式时包含桶名
+    path: String,
+    part_size: usize,
+    buffer: Vec<u8>,
+    upload_id: Option<String>,
+    etags: Vec<String>,
+    completed: bool,
+}
+
+struct Credentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+impl Credentials {
+    fn resolve(config: &S3Config) -> Result<Self> {
+        let access_key_id = match &config.access_key_id {
+            Some(id) => id.clone(),
+            None => env::var("AWS_ACCESS_KEY_ID")
+                .context("S3 access key not configured (export.s3.access_key_id or AWS_ACCESS_KEY_ID)")?,
+        };
+        let secret_access_key = match (&config.secret_access_key_env, &config.secret_access_key) {
+            (Some(name), _) => env::var(name).with_context(|| format!("Environment variable {} is not set", name))?,
+            (None, Some(secret)) => secret.clone(),
+            (None, None) => env::var("AWS_SECRET_ACCESS_KEY")
+                .context("S3 secret key not configured (export.s3.secret_access_key or AWS_SECRET_ACCESS_KEY)")?,
+        };
+        Ok(Self {
+            access_key_id,
+            secret_access_key,
+            session_token: env::var("AWS_SESSION_TOKEN").ok().filter(|t| !t.is_empty()),
+        })
+    }
+}
+
+impl S3Sink {
+    pub fn new(config: &S3Config, bucket: &str, key: &str) -> Result<Self> {
+        let credentials = Credentials::resolve(config)?;
+        let endpoint = config
+            .endpoint
+            .clone()
+            .unwrap_or_else(|| format!("https://s3.{}.amazonaws.com", config.region));
+        let endpoint = endpoint.trim_end_matches('/');
+        let Some((scheme, authority)) = endpoint.split_once("://") else {
+            bail!("Invalid S3 endpoint {} (expected http(s)://host[:port])", endpoint);
+        };
+        if authority.contains('/') {
+            bail!("S3 endpoint must not contain a path: {}", endpoint);
+        }
+
+        let key = uri_encode(key, true);
+        let (host, path) = if config.path_style.unwrap_or(config.endpoint.is_some()) {
+            (authority.to_string(), format!("/{}/{}", uri_encode(bucket, false), key))
+        } else {
+            (format!("{}.{}", bucket, authority), format!("/{}", key))
+        };
+
+        Ok(Self {
+            agent: ureq::AgentBuilder::new().build(),
+            credentials,
+            region: config.region.clone(),
+            base: format!("{}://{}", scheme, host),
+            host,
+            path,
+            part_size: config.part_size,
+            buffer: Vec::with_capacity(config.part_size),
+            upload_id: None,
+            etags: Vec::new(),
+            completed: false,
+        })
+    }
+
+    /// 发送签名请求，非2xx响应转为带响应内容的错误
+    fn send(&self, method: &str, query: &str, body: &[u8]) -> Result<ureq::Response> {
+        let now = chrono::Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = to_hex(&Sha256::digest(body));
+
+        let mut headers = vec![
+            ("host", self.host.clone()),
+            ("x-amz-content-sha256", payload_hash.clone()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = &self.credentials.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        let signed_headers = headers.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+            .collect();
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            method, self.path, query, canonical_headers, signed_headers, payload_hash
+        );
+
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            to_hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+        let mut key = hmac(format!("AWS4{}", self.credentials.secret_access_key).as_bytes(), date.as_bytes());
+        for part in [self.region.as_bytes(), b"s3", b"aws4_request"] {
+            key = hmac(&key, part);
+        }
+        let signature = to_hex(&hmac(&key, string_to_sign.as_bytes()));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.credentials.access_key_id, scope, signed_headers, signature
+        );
+
+        let url = if query.is_empty() {
+            format!("{}{}", self.base, self.path)
+        } else {
+            format!("{}{}?{}", self.base, self.path, query)
+        };
+        let mut request = self.agent.request(method, &url).set("Authorization", &authorization);
+        for (name, value) in &headers {
+            request = request.set(name, value);
+        }
+
+        match request.send_bytes(body) {
+            Ok(response) => Ok(response),
+            Err(ureq::Error::Status(status, response)) => {
+                let body = response.into_string().unwrap_or_default();
+                bail!("S3 {} {} failed with HTTP {}: {}", method, url, status, error_message(&body))
+            }
+            Err(e) => Err(anyhow!(e).context(format!("S3 {} {} failed", method, url))),
+        }
+    }
+
+    /// 上传缓冲区中的数据作为下一个分段，首次调用时创建分段上传
+    fn upload_part(&mut self) -> Result<()> {
+        if self.upload_id.is_none() {
+            let response = self.send("POST", "uploads=", b"")?;
+            let body = response.into_string()?;
+            let upload_id = xml_value(&body, "UploadId")
+                .ok_or_else(|| anyhow!("S3 did not return an UploadId: {}", body))?;
+            debug!("Started S3 multipart upload {}", upload_id);
+            self.upload_id = Some(upload_id);
+        }
+        if self.etags.len() >= MAX_PARTS {
+            bail!("S3 multipart upload exceeds {} parts, increase export.s3.part_size", MAX_PARTS);
+        }
+
+        let part_number = self.etags.len() + 1;
+        let query = format!(
+            "partNumber={}&uploadId={}",
+            part_number,
+            uri_encode(self.upload_id.as_deref().unwrap_or_default(), false)
+        );
+        let buffer = mem::replace(&mut self.buffer, Vec::with_capacity(self.part_size));
+        let response = self.send("PUT", &query, &buffer)?;
+        let etag = response
+            .header("ETag")
+            .ok_or_else(|| anyhow!("S3 did not return an ETag for part {}", part_number))?;
+        self.etags.push(etag.to_string());
+        Ok(())
+    }
+
+    fn complete(&mut self) -> Result<()> {
+        let Some(upload_id) = self.upload_id.clone() else {
+            let buffer = mem::take(&mut self.buffer);
+            self.send("PUT", "", &buffer)?;
+            self.completed = true;
+            return Ok(());
+        };
+
+        if !self.buffer.is_empty() {
+            self.upload_part()?;
+        }
+        let mut body = String::from("<CompleteMultipartUpload>");
+        for (i, etag) in self.etags.iter().enumerate() {
+            body.push_str(&format!("<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>", i + 1, etag));
+        }
+        body.push_str("</CompleteMultipartUpload>");
+
+        let query = format!("uploadId={}", uri_encode(&upload_id, false));
+        let response = self.send("POST", &query, body.as_bytes())?;
+        // 完成请求可能返回200但响应体为错误
+        let response = response.into_string()?;
+        if response.contains("<Error>") {
+            bail!("S3 failed to complete multipart upload: {}", error_message(&response));
+        }
+        self.completed = true;
+        debug!("Completed S3 multipart upload with {} part(s)", self.etags.len());
+        Ok(())
+    }
+}
+
+impl Write for S3Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= self.part_size {
+            self.upload_part().map_err(io_error)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // 分段不足最小大小时不能上传，数据在finish时提交
+        Ok(())
+    }
+}
+
+impl Sink for S3Sink {
+    fn finish(mut self: Box<Self>) -> Result<()> {
+        self.complete()
+    }
+}
+
+impl Drop for S3Sink {
+    /// 导出失败时放弃分段上传，避免残留的分段占用存储
+    fn drop(&mut self) {
+        if self.completed {
+            return;
+        }
+        if let Some(upload_id) = self.upload_id.take() {
+            let query = format!("uploadId={}", uri_encode(&upload_id, false));
+            if let Err(e) = self.send("DELETE", &query, b"") {
+                warn!("Failed to abort S3 multipart upload {}: {:#}", upload_id, e);
+            }
+        }
+    }
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 取XML中第一个 `<tag>` 的文本
+fn xml_value(xml: &str, tag: &str) -> Option<String> {
+    let open = format!("<{}>", tag);
+    let start = xml.find(&open)? + open.len();
+    let end = xml[start..].find(&format!("</{}>", tag))? + start;
+    Some(xml[start..end].to_string())
+}
+
+/// 从S3错误响应中提取错误码和说明
+fn error_message(body: &str) -> String {
+    match (xml_value(body, "Code"), xml_value(body, "Message")) {
+        (Some(code), Some(message)) => format!("{}: {}", code, message),
+        (Some(code), None) => code,
+        _ => body.trim().to_string(),
+    }
+}
@@ -0,0 +1,143 @@
+use super::Sink;
+use crate::config::SftpConfig;
+use anyhow::{bail, Context, Result};
+use ssh2::{CheckResult, KnownHostFileKind, Session, Sftp};
+use std::env;
+use std::io::{self, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// 通过SFTP写入远程文件。数据先写入同目录下的隐藏临时文件，完成后重命名，
+/// 下游不会读到不完整的文件
+pub struct SftpSink {
+    file: ssh2::File,
+    sftp: Sftp,
+    // 文件句柄依赖会话保持连接
+    _session: Session,
+    path: PathBuf,
+    temp_path: PathBuf,
+    finished: bool,
+}
+
+impl SftpSink {
+    pub fn connect(config: &SftpConfig, user: Option<&str>, host: &str, port: u16, path: &str) -> Result<Self> {
+        let user = match user {
+            Some(user) => user.to_string(),
+            None => env::var("USER").context("SFTP user not specified (use sftp://user@host/path)")?,
+        };
+
+        let tcp = TcpStream::connect((host, port)).with_context(|| format!("Failed to connect to {}:{}", host, port))?;
+        let mut session = Session::new()?;
+        session.set_tcp_stream(tcp);
+        session.handshake().with_context(|| format!("SSH handshake with {} failed", host))?;
+        verify_host_key(config, &session, host, port)?;
+
+        let password = match &config.password_env {
+            Some(name) => Some(env::var(name).with_context(|| format!("Environment variable {} is not set", name))?),
+            None => None,
+        };
+        if let Some(key_file) = &config.key_file {
+            session
+                .userauth_pubkey_file(&user, None, Path::new(key_file), password.as_deref())
+                .with_context(|| format!("SSH public key authentication failed for {}@{}", user, host))?;
+        } else if let Some(password) = &password {
+            session
+                .userauth_password(&user, password)
+                .with_context(|| format!("SSH password authentication failed for {}@{}", user, host))?;
+        } else {
+            session
+                .userauth_agent(&user)
+                .with_context(|| format!("SSH agent authentication failed for {}@{} (set export.sftp.key_file or password_env)", user, host))?;
+        }
+        if !session.authenticated() {
+            bail!("SSH authentication failed for {}@{}", user, host);
+        }
+
+        let sftp = session.sftp().context("Failed to start SFTP subsystem")?;
+        let path = PathBuf::from(path);
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let temp_path = path.with_file_name(format!(".{}.part", name));
+        let file = sftp
+            .create(&temp_path)
+            .with_context(|| format!("Failed to create remote file {}", temp_path.display()))?;
+        Ok(Self {
+            file,
+            sftp,
+            _session: session,
+            path,
+            temp_path,
+            finished: false,
+        })
+    }
+}
+
+/// 按known_hosts校验服务器主机密钥
+fn verify_host_key(config: &SftpConfig, session: &Session, host: &str, port: u16) -> Result<()> {
+    let known_hosts_file = match &config.known_hosts {
+        Some(path) => PathBuf::from(path),
+        None => Path::new(&env::var("HOME").unwrap_or_default()).join(".ssh/known_hosts"),
+    };
+    let Some((key, _)) = session.host_key() else {
+        bail!("SSH server {} did not present a host key", host);
+    };
+
+    let mut known_hosts = session.known_hosts()?;
+    if known_hosts_file.exists() {
+        known_hosts
+            .read_file(&known_hosts_file, KnownHostFileKind::OpenSSH)
+            .with_context(|| format!("Failed to read {}", known_hosts_file.display()))?;
+    }
+    match known_hosts.check_port(host, port, key) {
+        CheckResult::Match => Ok(()),
+        CheckResult::Mismatch => bail!("Host key for {} does not match {}", host, known_hosts_file.display()),
+        CheckResult::NotFound | CheckResult::Failure if config.accept_unknown_hosts => {
+            warn!("Host key for {} is not verified (export.sftp.accept_unknown_hosts = true)", host);
+            Ok(())
+        }
+        CheckResult::NotFound | CheckResult::Failure => bail!(
+            "Host {} not found in {} (add it with ssh-keyscan, or set export.sftp.accept_unknown_hosts for testing)",
+            host,
+            known_hosts_file.display()
+        ),
+    }
+}
+
+impl Write for SftpSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl Sink for SftpSink {
+    fn finish(mut self: Box<Self>) -> Result<()> {
+        self.file.flush()?;
+        self.file.close().context("Failed to close remote file")?;
+        // SFTP v3的服务器不支持覆盖重命名，先删除已存在的目标文件
+        if self.sftp.stat(&self.path).is_ok() {
+            self.sftp
+                .unlink(&self.path)
+                .with_context(|| format!("Failed to replace remote file {}", self.path.display()))?;
+        }
+        self.sftp
+            .rename(&self.temp_path, &self.path, None)
+            .with_context(|| format!("Failed to rename {} to {}", self.temp_path.display(), self.path.display()))?;
+        self.finished = true;
+        Ok(())
+    }
+}
+
+impl Drop for SftpSink {
+    /// 导出失败时删除临时文件
+    fn drop(&mut self) {
+        if !self.finished
+            && let Err(e) = self.sftp.unlink(&self.temp_path)
+        {
+            warn!("Failed to delete incomplete remote file {}: {}", self.temp_path.display(), e);
+        }
+    }
+}
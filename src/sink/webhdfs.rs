@@ -0,0 +1,158 @@
+use super::{io_error, uri_encode, Sink};
+use crate::config::WebHdfsConfig;
+use anyhow::{anyhow, bail, Result};
+use std::env;
+use std::io::{self, Write};
+use std::mem;
+use tracing::warn;
+
+/// 通过WebHDFS REST接口写入HDFS：首块以CREATE创建文件，后续数据块以APPEND追加。
+/// 数据先写入同目录下的隐藏临时文件，完成后重命名，下游不会读到不完整的文件
+pub struct WebHdfsSink {
+    agent: ureq::Agent,
+    /// `http(s)://namenode:port`
+    base: String,
+    path: String,
+    temp_path: String,
+    user: Option<String>,
+    chunk_size: usize,
+    buffer: Vec<u8>,
+    created: bool,
+    finished: bool,
+}
+
+impl WebHdfsSink {
+    pub fn new(config: &WebHdfsConfig, https: bool, authority: &str, path: &str) -> Self {
+        let scheme = if https { "https" } else { "http" };
+        let (dir, name) = path.rsplit_once('/').unwrap_or(("", path));
+        Self {
+            // NameNode以307重定向到DataNode，需要自行跟随以重发数据
+            agent: ureq::AgentBuilder::new().redirects(0).build(),
+            base: format!("{}://{}", scheme, authority),
+            path: path.to_string(),
+            temp_path: format!("{}/.{}._COPYING_", dir, name),
+            user: config
+                .user
+                .clone()
+                .or_else(|| env::var("HADOOP_USER_NAME").ok().filter(|u| !u.is_empty())),
+            chunk_size: config.chunk_size,
+            buffer: Vec::with_capacity(config.chunk_size),
+            created: false,
+            finished: false,
+        }
+    }
+
+    /// 文件操作的地址，`query` 为 `op=...` 及其参数
+    fn url(&self, path: &str, query: &str) -> String {
+        let mut url = format!("{}/webhdfs/v1{}?{}", self.base, uri_encode(path, true), query);
+        if let Some(user) = &self.user {
+            url.push_str(&format!("&user.name={}", uri_encode(user, false)));
+        }
+        url
+    }
+
+    /// 将缓冲区写入临时文件
+    fn send_chunk(&mut self) -> Result<()> {
+        let (method, op) = if self.created {
+            ("POST", "op=APPEND")
+        } else {
+            ("PUT", "op=CREATE&overwrite=true")
+        };
+        let url = self.url(&self.temp_path, op);
+
+        let response = self.call(method, &url, b"")?;
+        let location = match response.status() {
+            307 => response
+                .header("Location")
+                .ok_or_else(|| anyhow!("WebHDFS redirect without Location header"))?
+                .to_string(),
+            status => bail!("WebHDFS {} {} returned HTTP {} instead of a redirect", method, url, status),
+        };
+
+        let chunk = mem::replace(&mut self.buffer, Vec::with_capacity(self.chunk_size));
+        self.call(method, &location, &chunk)?;
+        self.created = true;
+        Ok(())
+    }
+
+    /// 删除已存在的目标文件后将临时文件重命名为目标文件
+    fn commit(&mut self) -> Result<()> {
+        self.call("DELETE", &self.url(&self.path, "op=DELETE"), b"")?;
+        let query = format!("op=RENAME&destination={}", uri_encode(&self.path, true));
+        let response = self.call("PUT", &self.url(&self.temp_path, &query), b"")?;
+        if !json_boolean(response)? {
+            bail!("WebHDFS failed to rename {} to {}", self.temp_path, self.path);
+        }
+        self.finished = true;
+        Ok(())
+    }
+
+    fn call(&self, method: &str, url: &str, body: &[u8]) -> Result<ureq::Response> {
+        let mut request = self.agent.request(method, url);
+        if !body.is_empty() {
+            request = request.set("Content-Type", "application/octet-stream");
+        }
+        match request.send_bytes(body) {
+            Ok(response) => Ok(response),
+            Err(ureq::Error::Status(status, response)) => {
+                let body = response.into_string().unwrap_or_default();
+                bail!("WebHDFS {} {} failed with HTTP {}: {}", method, url, status, remote_exception(&body))
+            }
+            Err(e) => Err(anyhow!(e).context(format!("WebHDFS {} {} failed", method, url))),
+        }
+    }
+}
+
+impl Write for WebHdfsSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= self.chunk_size {
+            self.send_chunk().map_err(io_error)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Sink for WebHdfsSink {
+    fn finish(mut self: Box<Self>) -> Result<()> {
+        // 空文件也需要创建
+        if !self.buffer.is_empty() || !self.created {
+            self.send_chunk()?;
+        }
+        self.commit()
+    }
+}
+
+impl Drop for WebHdfsSink {
+    /// 导出失败时删除临时文件
+    fn drop(&mut self) {
+        if self.finished || !self.created {
+            return;
+        }
+        let url = self.url(&self.temp_path, "op=DELETE");
+        if let Err(e) = self.call("DELETE", &url, b"") {
+            warn!("Failed to delete incomplete HDFS file {}: {:#}", self.temp_path, e);
+        }
+    }
+}
+
+/// WebHDFS操作返回的 `{"boolean": ...}` 结果
+fn json_boolean(response: ureq::Response) -> Result<bool> {
+    let body = response.into_string()?;
+    serde_json::from_str::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|v| v["boolean"].as_bool())
+        .ok_or_else(|| anyhow!("Unexpected WebHDFS response: {}", body))
+}
+
+/// 提取WebHDFS错误响应中的RemoteException说明
+fn remote_exception(body: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|v| v["RemoteException"]["message"].as_str().map(str::to_string))
+        .unwrap_or_else(|| body.trim().to_string())
+}
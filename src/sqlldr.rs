@@ -0,0 +1,271 @@
+use crate::config::{BlobMode, CompressionType, ExportConfig, ExportFormat, QuoteStyle};
+use crate::db::{ColumnInfo, ColumnKind};
+use crate::delimited::{parse_escapes, parse_single_byte};
+use crate::encoding;
+use crate::format::ColumnFormatter;
+use crate::masking::Masker;
+use crate::schema;
+use crate::sink;
+use crate::table::quote_identifier;
+use anyhow::{bail, Context, Result};
+use std::fmt::Write;
+use std::path::Path;
+use tracing::warn;
+
+/// 长度未知的字符列在控制文件中的最大长度（SQL*Loader默认为255）
+const DEFAULT_CHAR_LENGTH: u32 = 32767;
+/// 未限制长度的CLOB/LONG列的最大长度
+const DEFAULT_LOB_LENGTH: usize = 10 * 1024 * 1024;
+
+/// encoding_rs编码名称对应的Oracle字符集
+const CHARACTER_SETS: &[(&str, &str)] = &[
+    ("UTF-8", "AL32UTF8"),
+    ("GBK", "ZHS16GBK"),
+    ("gb18030", "ZHS32GB18030"),
+    ("Big5", "ZHT16MSWIN950"),
+    ("Shift_JIS", "JA16SJIS"),
+    ("EUC-JP", "JA16EUC"),
+    ("EUC-KR", "KO16MSWIN949"),
+    ("windows-1250", "EE8MSWIN1250"),
+    ("windows-1251", "CL8MSWIN1251"),
+    ("windows-1252", "WE8MSWIN1252"),
+    ("ISO-8859-2", "EE8ISO8859P2"),
+    ("ISO-8859-5", "CL8ISO8859P5"),
+    ("ISO-8859-15", "WE8ISO8859P15"),
+    ("KOI8-R", "CL8KOI8R"),
+];
+
+pub fn control_path(config: &ExportConfig) -> String {
+    schema::sidecar_path(&config.output_file, "ctl")
+}
+
+/// 写入SQL*Loader控制文件，返回文件路径；`columns` 为映射后的输出列
+pub fn write_control_file(
+    config: &ExportConfig,
+    columns: &[ColumnInfo],
+    formatter: &ColumnFormatter,
+    masker: &Masker,
+) -> Result<String> {
+    let content = render(config, columns, formatter, masker)?;
+    let path = control_path(config);
+    sink::write_file(config, &path, content.as_bytes()).with_context(|| format!("Failed to write control file: {}", path))?;
+    Ok(path)
+}
+
+/// 根据输出格式及列元数据生成控制文件内容
+pub fn render(config: &ExportConfig, columns: &[ColumnInfo], formatter: &ColumnFormatter, masker: &Masker) -> Result<String> {
+    if config.escape_char.is_some() {
+        bail!("SQL*Loader only supports doubled quotes inside enclosed fields (remove escape_char)");
+    }
+
+    let delimiter = match config.format {
+        ExportFormat::Tsv => b"\t".to_vec(),
+        _ => parse_escapes(&config.delimiter)?,
+    };
+    let terminator = match config.format {
+        ExportFormat::Custom => parse_escapes(&config.record_terminator)?,
+        _ => b"\n".to_vec(),
+    };
+
+    // SQL*Loader不能直接读取压缩文件，需先解压
+    let data_file = Path::new(&config.output_file);
+    let data_file = match config.compression {
+        CompressionType::Gzip => data_file.with_extension(""),
+        CompressionType::None => data_file.to_path_buf(),
+    };
+    let data_name = data_file
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let stem = data_file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let table = match config.source_table {
+        Some(ref table) => table.quoted(),
+        None => quote_identifier(&stem.to_uppercase()),
+    };
+
+    let mut out = String::new();
+    writeln!(out, "-- SQL*Loader control file for {}", data_name)?;
+    if matches!(config.compression, CompressionType::Gzip) {
+        writeln!(out, "-- Decompress the data file before loading: gunzip {}.gz", data_name)?;
+    }
+    if config.source_table.is_none() {
+        writeln!(out, "-- Target table derived from the file name, adjust as needed")?;
+    }
+    if config.include_header {
+        writeln!(out, "OPTIONS (SKIP=1)")?;
+    }
+    writeln!(out, "LOAD DATA")?;
+    let charset = encoding::lookup(&config.encoding)?.name();
+    match CHARACTER_SETS.iter().find(|(name, _)| *name == charset) {
+        Some((_, oracle)) => writeln!(out, "CHARACTERSET {}", oracle)?,
+        None => warn!("No Oracle character set known for {}, CHARACTERSET omitted from control file", charset),
+    }
+    if terminator == b"\n" {
+        writeln!(out, "INFILE '{}'", quote_string(&data_name))?;
+    } else {
+        writeln!(out, "INFILE '{}' \"STR X'{}'\"", quote_string(&data_name), hex(&terminator))?;
+    }
+    writeln!(out, "BADFILE '{}.bad'", quote_string(&stem))?;
+    writeln!(out, "DISCARDFILE '{}.dsc'", quote_string(&stem))?;
+    writeln!(out, "APPEND")?;
+    writeln!(out, "INTO TABLE {}", table)?;
+    write!(out, "FIELDS TERMINATED BY X'{}'", hex(&delimiter))?;
+    if config.quote_style != QuoteStyle::Never {
+        write!(out, " OPTIONALLY ENCLOSED BY X'{}'", hex(&[parse_single_byte(&config.quote_char)?]))?;
+    }
+    writeln!(out)?;
+    writeln!(out, "TRAILING NULLCOLS")?;
+    writeln!(out, "(")?;
+
+    let mut fields = Vec::with_capacity(columns.len());
+    let mut lob_files = Vec::new();
+    for (i, column) in columns.iter().enumerate() {
+        let name = quote_identifier(&column.name);
+        let rule = formatter.rule(i);
+        // 脱敏后的值不再是原类型，按文本加载
+        if masker.changes_type(i) {
+            fields.push(format!("{} CHAR({})", name, DEFAULT_CHAR_LENGTH));
+            continue;
+        }
+        let field = match column.kind {
+            ColumnKind::Date | ColumnKind::Timestamp | ColumnKind::TimestampTz => {
+                let mask = match rule.and_then(|r| r.format.as_deref()) {
+                    Some(format) => oracle_mask(format)
+                        .with_context(|| format!("Cannot derive a date mask for column {}", column.name))?,
+                    None => default_mask(column),
+                };
+                let datatype = match column.kind {
+                    // DATE掩码不支持FF，带小数秒时按TIMESTAMP读取
+                    ColumnKind::Date if mask.contains("FF") => "TIMESTAMP",
+                    ColumnKind::Date => "DATE",
+                    ColumnKind::Timestamp => "TIMESTAMP",
+                    _ => "TIMESTAMP WITH TIME ZONE",
+                };
+                format!("{} {} \"{}\" NULLIF {}=BLANKS", name, datatype, mask, name)
+            }
+            ColumnKind::Integer | ColumnKind::Number | ColumnKind::Float => {
+                if rule.is_some_and(|r| r.thousands_separator.is_some() || r.decimal_separator.is_some()) {
+                    warn!(
+                        "Column {} uses custom number separators, which SQL*Loader cannot parse without a mask",
+                        column.name
+                    );
+                }
+                format!("{} NULLIF {}=BLANKS", name, name)
+            }
+            ColumnKind::Blob | ColumnKind::Binary => match config.lob.blob {
+                BlobMode::Hex => format!("{} CHAR({})", name, column.size.map_or(DEFAULT_LOB_LENGTH, |n| n as usize * 2)),
+                BlobMode::File => {
+                    // 数据文件中为附属文件路径，通过LOBFILE读取内容
+                    let filler = quote_identifier(&format!("{}_FILE", column.name));
+                    lob_files.push(format!("{} LOBFILE({}) TERMINATED BY EOF NULLIF {}=BLANKS", name, filler, filler));
+                    format!("{} FILLER CHAR(4000)", filler)
+                }
+                BlobMode::Base64 => bail!(
+                    "Column {} is binary and exported as base64, which SQL*Loader cannot load (set blob = \"hex\" or \"file\")",
+                    column.name
+                ),
+            },
+            ColumnKind::Clob => {
+                let length = config
+                    .lob
+                    .max_length
+                    .map_or(DEFAULT_LOB_LENGTH, |n| n * 4 + config.lob.truncation_marker.len());
+                format!("{} CHAR({})", name, length)
+            }
+            // 列长度可能按字符计，多字节字符集下按最大4字节估算
+            _ => format!("{} CHAR({})", name, column.size.map_or(DEFAULT_CHAR_LENGTH, |n| n.saturating_mul(4).max(1))),
+        };
+        fields.push(field);
+    }
+    fields.extend(lob_files);
+
+    for (i, field) in fields.iter().enumerate() {
+        let separator = if i + 1 < fields.len() { "," } else { "" };
+        writeln!(out, "  {}{}", field, separator)?;
+    }
+    writeln!(out, ")")?;
+    Ok(out)
+}
+
+/// 未配置格式时与默认输出 `YYYY-MM-DD HH24:MI:SS[.FF] [TZH:TZM]` 对应的掩码
+fn default_mask(column: &ColumnInfo) -> String {
+    let mut mask = "YYYY-MM-DD HH24:MI:SS".to_string();
+    if column.kind != ColumnKind::Date && column.scale.is_none_or(|fs| fs > 0) {
+        mask.push_str(".FF");
+    }
+    if column.kind == ColumnKind::TimestampTz {
+        mask.push_str(" TZH:TZM");
+    }
+    mask
+}
+
+/// 将strftime格式转换为Oracle日期掩码
+fn oracle_mask(format: &str) -> Result<String> {
+    let mut mask = String::new();
+    let mut chars = format.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '%' {
+            if ch.is_alphanumeric() {
+                // 掩码中的字面文本需加双引号，控制文件中写为 \"
+                write!(mask, "\\\"{}\\\"", ch)?;
+            } else if ch == '"' {
+                bail!("double quotes are not supported in date formats");
+            } else {
+                mask.push(ch);
+            }
+            continue;
+        }
+
+        let mut spec = String::new();
+        while let Some(&next) = chars.peek() {
+            spec.push(next);
+            chars.next();
+            if next.is_ascii_alphabetic() || next == '%' {
+                break;
+            }
+        }
+        let part = match spec.as_str() {
+            "Y" => "YYYY",
+            "y" => "YY",
+            "m" => "MM",
+            "d" => "DD",
+            "H" => "HH24",
+            "I" => "HH12",
+            "M" => "MI",
+            "S" => "SS",
+            "p" => "AM",
+            "j" => "DDD",
+            "b" | "h" => "MON",
+            "B" => "MONTH",
+            "a" => "DY",
+            "A" => "DAY",
+            "F" => "YYYY-MM-DD",
+            "T" => "HH24:MI:SS",
+            "R" => "HH24:MI",
+            ".f" => ".FF",
+            ".3f" => ".FF3",
+            ".6f" => ".FF6",
+            ".9f" => ".FF9",
+            "3f" => "FF3",
+            "6f" => "FF6",
+            "9f" | "f" => "FF9",
+            "z" => "TZHTZM",
+            ":z" => "TZH:TZM",
+            other => bail!("format specifier %{} has no Oracle equivalent", other),
+        };
+        mask.push_str(part);
+    }
+    Ok(mask)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn quote_string(text: &str) -> String {
+    text.replace('\'', "''")
+}
@@ -0,0 +1,238 @@
+use crate::config::{CompressionType, ExportConfig, ExportFormat};
+use crate::db::oracle::OracleDatabase;
+use crate::sink::Target;
+use anyhow::{bail, Result};
+use std::path::Path;
+
+/// 表名规格，格式: [SCHEMA.]TABLE，表名中包含 `%` 或 `?` 时视为模式
+#[derive(Debug, Clone)]
+pub struct TableSpec {
+    pub owner: Option<String>,
+    pub name: String,
+}
+
+impl TableSpec {
+    pub fn parse(input: &str) -> Result<Self> {
+        let input = input.trim();
+        if input.is_empty() {
+            bail!("Table name cannot be empty");
+        }
+
+        let parts = split_qualified(input);
+        match parts.as_slice() {
+            [name] => Ok(Self {
+                owner: None,
+                name: normalize_identifier(name),
+            }),
+            [owner, name] => Ok(Self {
+                owner: Some(normalize_identifier(owner)),
+                name: normalize_identifier(name),
+            }),
+            _ => bail!("Invalid table name: {} (expected SCHEMA.TABLE)", input),
+        }
+    }
+
+    pub fn is_pattern(&self) -> bool {
+        self.name.contains(['%', '?'])
+    }
+
+    /// 按 `ESCAPE '\'` 转义的LIKE模式：`%` 匹配任意个字符，`?` 匹配单个字符，
+    /// 表名中常见的 `_` 按字面匹配
+    pub fn like_pattern(&self) -> String {
+        let mut pattern = String::with_capacity(self.name.len());
+        for ch in self.name.chars() {
+            match ch {
+                '\\' => pattern.push_str("\\\\"),
+                '_' => pattern.push_str("\\_"),
+                '?' => pattern.push('_'),
+                other => pattern.push(other),
+            }
+        }
+        pattern
+    }
+}
+
+/// 已解析到数据字典中的表
+#[derive(Debug, Clone)]
+pub struct TableRef {
+    pub owner: String,
+    pub name: String,
+}
+
+impl TableRef {
+    pub fn quoted(&self) -> String {
+        format!("{}.{}", quote_identifier(&self.owner), quote_identifier(&self.name))
+    }
+}
+
+/// 按"."拆分限定名，忽略双引号内的"."
+fn split_qualified(input: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+    for (i, ch) in input.char_indices() {
+        match ch {
+            '"' => in_quotes = !in_quotes,
+            '.' if !in_quotes => {
+                parts.push(&input[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&input[start..]);
+    parts
+}
+
+/// 未加引号的标识符按Oracle规则转为大写，加引号的保持原样
+fn normalize_identifier(ident: &str) -> String {
+    let ident = ident.trim();
+    if ident.len() >= 2 && ident.starts_with('"') && ident.ends_with('"') {
+        ident[1..ident.len() - 1].to_string()
+    } else {
+        ident.to_uppercase()
+    }
+}
+
+pub fn quote_identifier(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+pub fn build_query(table: &TableRef, columns: &[String], where_clause: Option<&str>) -> String {
+    let column_list = columns
+        .iter()
+        .map(|c| quote_identifier(c))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut query = format!("SELECT {} FROM {}", column_list, table.quoted());
+    if let Some(filter) = where_clause.map(str::trim).filter(|w| !w.is_empty()) {
+        query.push_str(" WHERE ");
+        query.push_str(filter);
+    }
+    query
+}
+
+fn file_extension(format: &ExportFormat, compression: &CompressionType) -> String {
+    let ext = match format {
+        ExportFormat::Csv => "csv",
+        ExportFormat::Tsv => "tsv",
+        ExportFormat::Custom => "txt",
+        ExportFormat::Sql => "sql",
+        ExportFormat::Xlsx => "xlsx",
+        ExportFormat::ArrowIpc => "arrow",
+        ExportFormat::Avro => "avro",
+        ExportFormat::FixedWidth => "txt",
+    };
+    match compression {
+        CompressionType::Gzip => format!("{}.gz", ext),
+        CompressionType::None => ext.to_string(),
+    }
+}
+
+/// 计算表的输出文件路径
+/// - 未指定output_file: 使用 `<TABLE>.<ext>`
+/// - output_file包含 `{table}`: 替换为表名
+/// - 多表导出且无占位符: output_file视为输出目录（远程地址时为目录前缀）
+/// - `-`（标准输出）: 只能导出一张表
+fn output_file_for(config: &ExportConfig, table: &TableRef, multiple: bool) -> Result<String> {
+    let default_name = format!(
+        "{}.{}",
+        table.name,
+        file_extension(&config.format, &config.compression)
+    );
+
+    if config.output_file.is_empty() {
+        return Ok(default_name);
+    }
+    if config.writes_to_stdout() {
+        if multiple {
+            bail!("output_file = \"-\" (stdout) can only be used when exporting a single table");
+        }
+        return Ok(config.output_file.clone());
+    }
+    if config.output_file.contains("{table}") {
+        return Ok(config.output_file.replace("{table}", &table.name));
+    }
+    if multiple && !Target::parse(&config.output_file)?.is_local() {
+        return Ok(format!("{}/{}", config.output_file.trim_end_matches('/'), default_name));
+    }
+    if multiple {
+        // 目录在导出时创建，预演不会创建
+        let dir = Path::new(&config.output_file);
+        return Ok(dir.join(default_name).to_string_lossy().into_owned());
+    }
+    Ok(config.output_file.clone())
+}
+
+/// 计算表的LOB附属文件目录：替换 `{table}` 占位符，多表导出且无占位符时按表名分子目录
+fn lob_dir_for(config: &ExportConfig, table: &TableRef, multiple: bool) -> String {
+    if config.lob.dir.contains("{table}") {
+        return config.lob.dir.replace("{table}", &table.name);
+    }
+    if multiple {
+        return format!("{}/{}", config.lob.dir.trim_end_matches(['/', '\\']), table.name);
+    }
+    config.lob.dir.clone()
+}
+
+/// 解析表名列表，为每张表生成独立的导出配置
+pub fn plan_exports(db: &mut OracleDatabase, config: &ExportConfig) -> Result<Vec<ExportConfig>> {
+    let mut tables = Vec::new();
+    for input in &config.tables {
+        let spec = TableSpec::parse(input)?;
+        let resolved = db.resolve_tables(&spec)?;
+        if resolved.is_empty() {
+            bail!("No table found matching: {}", input);
+        }
+        tables.extend(resolved);
+    }
+
+    let multiple = tables.len() > 1;
+    let mut jobs = Vec::with_capacity(tables.len());
+    for table in &tables {
+        let table_columns = db.table_columns(table)?;
+
+        let columns = if config.table_columns.is_empty() {
+            table_columns
+        } else {
+            let mut selected = Vec::with_capacity(config.table_columns.len());
+            for column in &config.table_columns {
+                let name = normalize_identifier(column);
+                if !table_columns.contains(&name) {
+                    bail!("Column {} not found in table {}.{}", name, table.owner, table.name);
+                }
+                selected.push(name);
+            }
+            selected
+        };
+
+        let mut job = config.clone();
+        job.tables = Vec::new();
+        job.query = build_query(table, &columns, config.where_clause.as_deref());
+        job.output_file = output_file_for(config, table, multiple)?;
+        job.lob.dir = lob_dir_for(config, table, multiple);
+        job.source_table = Some(table.clone());
+        jobs.push(job);
+    }
+
+    Ok(jobs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn underscore_is_literal_in_patterns() {
+        let spec = TableSpec::parse("hr.order_%").unwrap();
+        assert!(spec.is_pattern());
+        assert_eq!(spec.like_pattern(), "ORDER\\_%");
+
+        let spec = TableSpec::parse("HR.JOB?").unwrap();
+        assert!(spec.is_pattern());
+        assert_eq!(spec.like_pattern(), "JOB_");
+
+        assert!(!TableSpec::parse("HR.ORDER_ITEMS").unwrap().is_pattern());
+    }
+}
@@ -0,0 +1,103 @@
+use crate::config::ExportConfig;
+use crate::db::{ColumnInfo, ColumnKind, Value};
+use crate::expr::{self, Expr};
+use anyhow::{bail, Context, Result};
+
+/// 按 `filter` 过滤行，并在查询结果之后追加 `[export.computed]` 计算列
+pub struct RowTransformer {
+    filter: Option<Expr>,
+    computed: Vec<Expr>,
+    computed_columns: Vec<ColumnInfo>,
+}
+
+impl RowTransformer {
+    /// 计算列只能引用查询结果中的列；过滤条件可以同时引用查询结果列和计算列
+    pub fn new(config: &ExportConfig, columns: &[ColumnInfo]) -> Result<Self> {
+        let mut computed = Vec::with_capacity(config.computed.len());
+        let mut computed_columns = Vec::with_capacity(config.computed.len());
+        for (name, text) in &config.computed {
+            if columns.iter().any(|c| c.name.eq_ignore_ascii_case(name)) {
+                bail!("Computed column {} conflicts with a column in the query result", name);
+            }
+            let mut expr = Expr::parse(text).with_context(|| format!("Invalid expression for computed column {}", name))?;
+            let kind = expr
+                .bind(columns)
+                .with_context(|| format!("Invalid expression for computed column {}", name))?;
+            computed.push(expr);
+            computed_columns.push(ColumnInfo {
+                name: name.clone(),
+                type_name: type_name(kind),
+                kind,
+                precision: None,
+                scale: None,
+                size: None,
+                nullable: true,
+            });
+        }
+
+        let filter = match config.filter {
+            Some(ref text) => {
+                let all_columns: Vec<ColumnInfo> = columns.iter().chain(&computed_columns).cloned().collect();
+                let mut expr = Expr::parse(text).context("Invalid filter expression")?;
+                let kind = expr.bind(&all_columns).context("Invalid filter expression")?;
+                if kind != ColumnKind::Boolean {
+                    bail!(
+                        "Filter must be a condition such as AMOUNT > 0, got a {} expression: {}",
+                        kind.name(),
+                        text
+                    );
+                }
+                Some(expr)
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            filter,
+            computed,
+            computed_columns,
+        })
+    }
+
+    /// 查询结果列加上计算列
+    pub fn columns(&self, columns: &[ColumnInfo]) -> Vec<ColumnInfo> {
+        columns.iter().chain(&self.computed_columns).cloned().collect()
+    }
+
+    pub fn has_filter(&self) -> bool {
+        self.filter.is_some()
+    }
+
+    /// 追加计算列并按过滤条件判断，条件不为真（包括NULL）时返回None
+    pub fn apply(&self, mut values: Vec<Value>) -> Result<Option<Vec<Value>>> {
+        if !self.computed.is_empty() {
+            let mut extra = Vec::with_capacity(self.computed.len());
+            for (expr, column) in self.computed.iter().zip(&self.computed_columns) {
+                extra.push(
+                    expr.eval(&values)
+                        .with_context(|| format!("Failed to evaluate computed column {}", column.name))?,
+                );
+            }
+            values.extend(extra);
+        }
+        if let Some(ref filter) = self.filter {
+            let keep = expr::truth(&filter.eval(&values).context("Failed to evaluate filter")?);
+            if keep != Some(true) {
+                return Ok(None);
+            }
+        }
+        Ok(Some(values))
+    }
+}
+
+/// 计算列在表结构附属文件中的类型
+fn type_name(kind: ColumnKind) -> String {
+    match kind {
+        ColumnKind::Text => "VARCHAR2(4000)".to_string(),
+        ColumnKind::Integer | ColumnKind::Number | ColumnKind::Float => "NUMBER".to_string(),
+        ColumnKind::Date => "DATE".to_string(),
+        ColumnKind::Timestamp => "TIMESTAMP".to_string(),
+        ColumnKind::TimestampTz => "TIMESTAMP WITH TIME ZONE".to_string(),
+        other => other.name().to_uppercase(),
+    }
+}
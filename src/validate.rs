@@ -0,0 +1,542 @@
+use crate::avro;
+use crate::config::{BlobMode, CompressionType, ConfigSource, DatabaseConfig, ExportConfig, ExportFormat, MaskMethod, SanitizeMode, SchemaOutput};
+use crate::delimited::{parse_escapes, parse_single_byte};
+use crate::encoding;
+use crate::expr::Expr;
+use crate::format;
+use crate::masking;
+use crate::sink::Target;
+use anyhow::{bail, Result};
+use std::collections::BTreeMap;
+
+/// 支持的数据库类型
+const SUPPORTED_DB_TYPES: &[&str] = &["oracle"];
+
+/// S3分段上传的最小分段大小（最后一个分段除外）
+const MIN_S3_PART_SIZE: usize = 5 * 1024 * 1024;
+
+struct Issue {
+    key: String,
+    source: ConfigSource,
+    message: String,
+}
+
+/// 配置校验器，根据配置项的来源汇总所有问题后统一报错
+pub struct Validator {
+    sources: BTreeMap<String, ConfigSource>,
+    issues: Vec<Issue>,
+}
+
+impl Validator {
+    pub fn new(sources: BTreeMap<String, ConfigSource>) -> Self {
+        Self {
+            sources,
+            issues: Vec::new(),
+        }
+    }
+
+    pub fn error(&mut self, key: &str, message: impl Into<String>) {
+        // 表类型的配置项（如 export.columns.types.date）取其下任一配置项的来源
+        let prefix = format!("{}.", key);
+        let source = self
+            .sources
+            .get(key)
+            .or_else(|| {
+                self.sources
+                    .range(prefix.clone()..)
+                    .next()
+                    .filter(|(k, _)| k.starts_with(&prefix))
+                    .map(|(_, source)| source)
+            })
+            .cloned()
+            .unwrap_or(ConfigSource::Default);
+        self.issues.push(Issue {
+            key: key.to_string(),
+            source,
+            message: message.into(),
+        });
+    }
+
+    pub fn check_database(&mut self, db: &DatabaseConfig) {
+        if !SUPPORTED_DB_TYPES.contains(&db.db_type.to_lowercase().as_str()) {
+            self.error(
+                "database.db_type",
+                format!(
+                    "unsupported database type \"{}\" (supported: {})",
+                    db.db_type,
+                    SUPPORTED_DB_TYPES.join(", ")
+                ),
+            );
+        }
+        if db.connection_string.trim().is_empty() {
+            self.error("database.connection_string", "connection string is required");
+        }
+        if db.username.trim().is_empty() {
+            self.error("database.username", "username is required");
+        }
+        if db.password.is_empty() {
+            self.error("database.password", "password is required");
+        }
+        if db.fetch_size == 0 {
+            self.error("database.fetch_size", "must be greater than 0");
+        } else if db.fetch_size > u32::MAX as usize {
+            self.error("database.fetch_size", format!("must not exceed {}", u32::MAX));
+        }
+    }
+
+    pub fn check_export(&mut self, export: &ExportConfig) {
+        let table_mode = !export.tables.is_empty();
+        if table_mode {
+            if !export.query.is_empty() {
+                self.error("export.query", "query and tables cannot be used together");
+            }
+        } else {
+            if export.query.trim().is_empty() {
+                self.error("export.query", "query or tables is required");
+            }
+            if export.output_file.trim().is_empty() {
+                self.error("export.output_file", "output file is required");
+            }
+            if matches!(export.schema_output, SchemaOutput::Ddl) {
+                self.error("export.schema_output", "ddl schema output requires table mode (tables)");
+            }
+            if export.where_clause.is_some() {
+                self.error("export.where", "where is only valid in table mode (tables)");
+            }
+            if !export.table_columns.is_empty() {
+                self.error("export.table_columns", "table_columns is only valid in table mode (tables)");
+            }
+        }
+
+        // 写入标准输出时没有文件名可供控制文件引用或推断表名
+        if export.writes_to_stdout() {
+            if export.sqlldr {
+                self.error("export.sqlldr", "sqlldr requires an output file (not valid with output_file = \"-\")");
+            }
+            if !table_mode && matches!(export.format, ExportFormat::Sql) && export.sql.table.is_none() {
+                self.error("export.sql.table", "target table is required for sql format when writing to stdout");
+            }
+        }
+
+        self.check_delimiters(export);
+        self.check_encoding(export);
+        self.check_columns(export);
+        self.check_expressions(export);
+        self.check_mapping(export);
+        self.check_masking(export);
+        self.check_lob(export);
+        self.check_sink(export);
+        self.check_format(export);
+        if export.sqlldr && export.escape_char.is_some() {
+            self.error("export.escape_char", "SQL*Loader only supports doubled quotes, escape_char cannot be used with sqlldr");
+        }
+        if export.buffer_size == 0 {
+            self.error("export.buffer_size", "must be greater than 0");
+        }
+        if export.show_progress && export.progress_interval == 0 {
+            self.error("export.progress_interval", "must be greater than 0");
+        }
+
+        if export.as_of_scn.is_some() && export.as_of_timestamp.is_some() {
+            self.error("export.as_of_scn", "as_of_scn and as_of_timestamp cannot be used together");
+        }
+        if let Some(ref ts) = export.as_of_timestamp
+            && !is_valid_timestamp(ts)
+        {
+            self.error(
+                "export.as_of_timestamp",
+                format!("invalid timestamp {:?} (expected YYYY-MM-DD HH24:MI:SS)", ts),
+            );
+        }
+    }
+
+    /// CSV分隔符必须为单字节；自定义格式的分隔符和结束符可以是任意非空字节序列；
+    /// 引号及转义字符必须为单字节
+    fn check_delimiters(&mut self, export: &ExportConfig) {
+        let delimiter = parse_escapes(&export.delimiter);
+        match export.format {
+            ExportFormat::Csv => match delimiter {
+                Ok(bytes) if bytes.len() == 1 => {}
+                Ok(_) => self.error(
+                    "export.delimiter",
+                    format!("must be a single byte for csv, got {:?} (use format = \"custom\" for multi-byte delimiters)", export.delimiter),
+                ),
+                Err(e) => self.error("export.delimiter", e.to_string()),
+            },
+            ExportFormat::Custom => {
+                match delimiter {
+                    Ok(bytes) if bytes.is_empty() => self.error("export.delimiter", "must not be empty"),
+                    Ok(_) => {}
+                    Err(e) => self.error("export.delimiter", e.to_string()),
+                }
+                match parse_escapes(&export.record_terminator) {
+                    Ok(bytes) if bytes.is_empty() => self.error("export.record_terminator", "must not be empty"),
+                    Ok(_) => {}
+                    Err(e) => self.error("export.record_terminator", e.to_string()),
+                }
+            }
+            // 定长记录可以没有结束符
+            ExportFormat::FixedWidth => {
+                if let Err(e) = parse_escapes(&export.record_terminator) {
+                    self.error("export.record_terminator", e.to_string());
+                }
+            }
+            _ => {}
+        }
+        if !matches!(export.format, ExportFormat::Custom | ExportFormat::FixedWidth)
+            && !matches!(parse_escapes(&export.record_terminator).as_deref(), Ok(b"\n"))
+        {
+            self.error("export.record_terminator", "record_terminator is only valid for custom and fixed formats");
+        }
+
+        if let Err(e) = parse_single_byte(&export.quote_char) {
+            self.error("export.quote_char", e.to_string());
+        }
+        if let Some(ref escape) = export.escape_char
+            && let Err(e) = parse_single_byte(escape)
+        {
+            self.error("export.escape_char", e.to_string());
+        }
+        if export.sanitize != SanitizeMode::None && !matches!(export.format, ExportFormat::Custom) {
+            self.error("export.sanitize", "sanitize is only valid for custom format");
+        }
+        if let Err(e) = parse_escapes(&export.sanitize_replacement) {
+            self.error("export.sanitize_replacement", e.to_string());
+        }
+    }
+
+    /// 输出编码必须可写出；非UTF-8编码下引号、转义字符需为ASCII，替换内容需为文本，
+    /// 以便与字段内容一起正确转换
+    fn check_encoding(&mut self, export: &ExportConfig) {
+        let encoding = match encoding::lookup(&export.encoding) {
+            Ok(encoding) => encoding,
+            Err(e) => {
+                self.error("export.encoding", e.to_string());
+                return;
+            }
+        };
+        if encoding == encoding_rs::UTF_8 {
+            return;
+        }
+
+        if export.bom {
+            self.error("export.bom", format!("bom is only valid for utf-8 encoding, got {}", encoding.name()));
+        }
+        if let Ok(quote) = parse_single_byte(&export.quote_char)
+            && !quote.is_ascii()
+        {
+            self.error("export.quote_char", format!("must be ASCII when encoding is {}", encoding.name()));
+        }
+        if let Some(Ok(escape)) = export.escape_char.as_deref().map(parse_single_byte)
+            && !escape.is_ascii()
+        {
+            self.error("export.escape_char", format!("must be ASCII when encoding is {}", encoding.name()));
+        }
+        // 分隔符按原始字节写出，不经过编码转换；非ASCII字符会以UTF-8字节出现在文件中
+        for (key, value) in [("export.delimiter", &export.delimiter), ("export.record_terminator", &export.record_terminator)] {
+            if let Ok(bytes) = parse_escapes(value)
+                && std::str::from_utf8(&bytes).is_ok_and(|text| !text.is_ascii())
+            {
+                self.error(key, format!("must be ASCII when encoding is {} (use \\x escapes for raw bytes)", encoding.name()));
+            }
+        }
+        if export.sanitize == SanitizeMode::Replace
+            && let Ok(bytes) = parse_escapes(&export.sanitize_replacement)
+            && String::from_utf8(bytes).is_err()
+        {
+            self.error(
+                "export.sanitize_replacement",
+                format!("must be valid text (no raw \\x bytes) when encoding is {}", encoding.name()),
+            );
+        }
+    }
+
+    /// 类型规则需为已知类型且各项适用于该类型；列名规则只能检查取值，
+    /// 是否适用要在获取到查询的列类型后检查
+    fn check_columns(&mut self, export: &ExportConfig) {
+        for (name, rule) in &export.columns.types {
+            let key = format!("export.columns.types.{}", name);
+            match format::kind_from_name(name) {
+                Some(kind) => {
+                    if let Err(message) = format::check_rule(rule, kind, false) {
+                        self.error(&key, message);
+                    }
+                }
+                None => self.error(
+                    &key,
+                    format!(
+                        "unknown column type \"{}\" (expected {})",
+                        name,
+                        format::FORMAT_TYPES.iter().map(|k| k.name()).collect::<Vec<_>>().join("/")
+                    ),
+                ),
+            }
+        }
+        for (name, rule) in &export.columns.names {
+            if let Err(message) = format::check_syntax(rule) {
+                self.error(&format!("export.columns.names.{}", name), message);
+            }
+        }
+    }
+
+    /// 只检查语法，列名在获取查询列信息后解析
+    fn check_expressions(&mut self, export: &ExportConfig) {
+        if let Some(ref filter) = export.filter
+            && let Err(e) = Expr::parse(filter)
+        {
+            self.error("export.filter", format!("invalid expression: {}", e));
+        }
+        for (name, text) in &export.computed {
+            let key = format!("export.computed.{}", name);
+            if name.trim().is_empty() {
+                self.error(&key, "computed column name must not be empty");
+            }
+            if let Err(e) = Expr::parse(text) {
+                self.error(&key, format!("invalid expression: {}", e));
+            }
+        }
+    }
+
+    /// 映射中的列是否存在要在获取到查询的列信息后检查，这里只检查列表之间的冲突
+    fn check_mapping(&mut self, export: &ExportConfig) {
+        let mapping = &export.mapping;
+        let same = |a: &String, b: &String| a.eq_ignore_ascii_case(b);
+
+        for (key, list) in [("export.mapping.include", &mapping.include), ("export.mapping.order", &mapping.order)] {
+            if let Some(dup) = list.iter().enumerate().find(|(i, name)| list[..*i].iter().any(|n| same(n, name))) {
+                self.error(key, format!("column {} is listed more than once", dup.1));
+            }
+        }
+        for name in &mapping.exclude {
+            if mapping.include.iter().any(|n| same(n, name)) {
+                self.error("export.mapping.exclude", format!("column {} is both included and excluded", name));
+            }
+            if mapping.order.iter().any(|n| same(n, name)) {
+                self.error("export.mapping.order", format!("column {} is excluded and cannot be ordered", name));
+            }
+        }
+        if !mapping.include.is_empty() {
+            for name in &mapping.order {
+                if !mapping.include.iter().any(|n| same(n, name)) {
+                    self.error("export.mapping.order", format!("column {} is not in include", name));
+                }
+            }
+        }
+        for (name, output) in &mapping.rename {
+            if output.trim().is_empty() {
+                self.error(&format!("export.mapping.rename.{}", name), "output column name must not be empty");
+            }
+        }
+    }
+
+    fn check_masking(&mut self, export: &ExportConfig) {
+        let masking = &export.masking;
+        for (name, rule) in &masking.columns {
+            let key = format!("export.masking.columns.{}", name);
+            let partial = rule.keep_start.is_some() || rule.keep_end.is_some() || rule.mask_char.is_some();
+            if partial && rule.method != MaskMethod::Partial {
+                self.error(&key, "keep_start/keep_end/mask_char only apply to method \"partial\"");
+            }
+            if (rule.prefix.is_some() || rule.length.is_some()) && rule.method != MaskMethod::Token {
+                self.error(&key, "prefix/length only apply to method \"token\"");
+            }
+            if let Some(ref mask_char) = rule.mask_char
+                && mask_char.chars().count() != 1
+            {
+                self.error(&format!("{}.mask_char", key), format!("must be a single character, got {:?}", mask_char));
+            }
+            if let Some(length) = rule.length
+                && !(1..=64).contains(&length)
+            {
+                self.error(&format!("{}.length", key), format!("must be between 1 and 64, got {}", length));
+            }
+        }
+
+        let salted = masking.columns.values().any(|rule| masking::needs_salt(rule.method));
+        if let Some(ref var) = masking.salt_env {
+            if std::env::var_os(var).is_none() {
+                self.error("export.masking.salt_env", format!("environment variable {} is not set", var));
+            }
+        } else if salted && masking.salt.is_none() {
+            self.error(
+                "export.masking.salt",
+                "a salt is required for sha256/hmac/fake/token (set salt or salt_env)",
+            );
+        }
+        if masking.salt.as_deref() == Some("") {
+            self.error("export.masking.salt", "must not be empty");
+        }
+    }
+
+    fn check_lob(&mut self, export: &ExportConfig) {
+        if export.lob.max_length == Some(0) {
+            self.error("export.lob.max_length", "must be greater than 0");
+        }
+        if export.lob.blob == BlobMode::File && export.lob.dir.trim().is_empty() {
+            self.error("export.lob.dir", "directory is required when blob = \"file\"");
+        }
+    }
+
+    /// 远程输出（s3/webhdfs/sftp）：地址格式、分段大小及凭据环境变量
+    fn check_sink(&mut self, export: &ExportConfig) {
+        let target = match Target::parse(&export.output_file) {
+            Ok(target) => target,
+            Err(e) => {
+                self.error("export.output_file", format!("{:#}", e));
+                return;
+            }
+        };
+        if target.is_local() {
+            return;
+        }
+        // LOB附属文件按本地路径写入
+        if export.lob.blob == BlobMode::File {
+            self.error("export.lob.blob", "blob = \"file\" requires a local output file");
+        }
+        match target {
+            Target::S3 { .. } => {
+                if export.s3.part_size < MIN_S3_PART_SIZE {
+                    self.error("export.s3.part_size", "must be at least 5 MiB (5242880)");
+                }
+                if let Some(ref var) = export.s3.secret_access_key_env
+                    && std::env::var(var).is_err()
+                {
+                    self.error("export.s3.secret_access_key_env", format!("environment variable {} is not set", var));
+                }
+            }
+            Target::WebHdfs { .. } => {
+                if export.webhdfs.chunk_size == 0 {
+                    self.error("export.webhdfs.chunk_size", "must be greater than 0");
+                }
+            }
+            Target::Sftp { .. } => {
+                if let Some(ref var) = export.sftp.password_env
+                    && std::env::var(var).is_err()
+                {
+                    self.error("export.sftp.password_env", format!("environment variable {} is not set", var));
+                }
+            }
+            Target::Stdout | Target::File(_) => {}
+        }
+    }
+
+    /// SQL、XLSX、Arrow及Avro格式按值的类型写入（字面量、单元格类型或列类型），不使用文本格式化规则
+    fn check_format(&mut self, export: &ExportConfig) {
+        let sql = &export.sql;
+        if sql.batch_size == 0 {
+            self.error("export.sql.batch_size", "must be greater than 0");
+        }
+        if sql.table.as_deref().is_some_and(|t| t.trim().is_empty()) {
+            self.error("export.sql.table", "must not be empty");
+        }
+
+        if export.arrow.batch_size == Some(0) {
+            self.error("export.arrow.batch_size", "must be greater than 0");
+        }
+        if export.arrow.number_scale > 38 {
+            self.error("export.arrow.number_scale", "must not exceed 38");
+        }
+
+        let avro = &export.avro;
+        if avro.block_size == 0 {
+            self.error("export.avro.block_size", "must be greater than 0");
+        }
+        if avro.name.as_deref().is_some_and(|n| n.contains('.') || !avro::is_valid_name(n)) {
+            self.error("export.avro.name", "must start with a letter or _ and contain only letters, digits and _");
+        }
+        if avro.namespace.as_deref().is_some_and(|n| !avro::is_valid_name(n)) {
+            self.error("export.avro.namespace", "must be dot-separated names of letters, digits and _");
+        }
+
+        self.check_fixed(export);
+
+        let format = match export.format {
+            ExportFormat::Sql => "sql",
+            ExportFormat::Xlsx => "xlsx",
+            ExportFormat::ArrowIpc => "arrow",
+            ExportFormat::Avro => "avro",
+            _ => return,
+        };
+        if !export.columns.types.is_empty() || !export.columns.names.is_empty() {
+            self.error("export.columns", format!("formatting rules do not apply to {} format (values keep their types)", format));
+        }
+        if export.sqlldr {
+            self.error("export.sqlldr", "sqlldr is only valid for csv/tsv/custom formats");
+        }
+        if matches!(export.format, ExportFormat::Sql) && export.include_header {
+            self.error("export.include_header", "include_header is not valid for sql format");
+        }
+        if matches!(export.format, ExportFormat::Xlsx) && matches!(export.compression, CompressionType::Gzip) {
+            self.error("export.compression", "xlsx files are already compressed, use compression = \"none\"");
+        }
+        if matches!(export.format, ExportFormat::Avro) && matches!(export.compression, CompressionType::Gzip) {
+            self.error("export.compression", "avro blocks are compressed by export.avro.codec, use compression = \"none\"");
+        }
+        if matches!(export.format, ExportFormat::Xlsx | ExportFormat::ArrowIpc | ExportFormat::Avro) {
+            if encoding::lookup(&export.encoding).is_ok_and(|e| e != encoding_rs::UTF_8) {
+                self.error("export.encoding", format!("{} files are always utf-8", format));
+            }
+            if export.bom {
+                self.error("export.bom", format!("bom is not valid for {} format", format));
+            }
+        }
+    }
+
+    /// 定长格式：填充字符为单个ASCII字符，列宽大于0
+    fn check_fixed(&mut self, export: &ExportConfig) {
+        let fixed = &export.fixed;
+        if !is_pad_char(&fixed.pad) {
+            self.error("export.fixed.pad", format!("must be a single ASCII character, got {:?}", fixed.pad));
+        }
+        for (name, column) in &fixed.columns {
+            let key = format!("export.fixed.columns.{}", name);
+            if column.width == Some(0) {
+                self.error(&format!("{}.width", key), "must be greater than 0");
+            }
+            if let Some(ref pad) = column.pad
+                && !is_pad_char(pad)
+            {
+                self.error(&format!("{}.pad", key), format!("must be a single ASCII character, got {:?}", pad));
+            }
+        }
+
+        if !matches!(export.format, ExportFormat::FixedWidth) {
+            return;
+        }
+        if export.sqlldr {
+            self.error("export.sqlldr", "sqlldr is only valid for csv/tsv/custom formats");
+        }
+        if export.bom {
+            self.error("export.bom", "bom is not valid for fixed format (it would shift the first record)");
+        }
+    }
+
+    /// 存在问题时返回包含所有问题的错误
+    pub fn finish(self) -> Result<()> {
+        if self.issues.is_empty() {
+            return Ok(());
+        }
+
+        let mut message = format!("Invalid configuration ({} problem(s)):", self.issues.len());
+        for issue in &self.issues {
+            message.push_str(&format!("\n  - {} ({}): {}", issue.key, issue.source, issue.message));
+        }
+        bail!(message)
+    }
+}
+
+fn is_pad_char(pad: &str) -> bool {
+    matches!(parse_escapes(pad).as_deref(), Ok([b]) if b.is_ascii())
+}
+
+/// 校验 `YYYY-MM-DD HH24:MI:SS` 格式
+fn is_valid_timestamp(ts: &str) -> bool {
+    let bytes = ts.as_bytes();
+    bytes.len() == 19
+        && bytes.iter().enumerate().all(|(i, b)| match i {
+            4 | 7 => *b == b'-',
+            10 => *b == b' ',
+            13 | 16 => *b == b':',
+            _ => b.is_ascii_digit(),
+        })
+}
@@ -0,0 +1,94 @@
+use crate::arrow::ArrowWriter;
+use crate::avro::AvroWriter;
+use crate::config::{ExportConfig, ExportFormat};
+use crate::db::{ColumnInfo, ColumnKind, Value};
+use crate::delimited::DelimitedWriter;
+use crate::fixed::{FixedWidthWriter, Layout};
+use crate::format::ColumnFormatter;
+use crate::insert::InsertWriter;
+use crate::lob;
+use crate::masking::Masker;
+use crate::xlsx::XlsxWriter;
+use anyhow::Result;
+use std::io::Write;
+
+/// 输出格式的行写入器，接收脱敏后的字段值
+pub trait RowWriter {
+    /// 写入数据之前的内容（表头、脚本开头等），`headers` 为输出列名
+    fn begin(&mut self, out: &mut dyn Write, headers: &[String]) -> Result<()>;
+    fn write_row(&mut self, out: &mut dyn Write, values: Vec<Value>) -> Result<()>;
+    /// 写入缓冲中剩余的行及结尾内容
+    fn finish(&mut self, out: &mut dyn Write) -> Result<()>;
+}
+
+/// 按导出格式创建写入器，`columns` 为 [`value_columns`] 返回的列，`formatter` 用于文本及定长格式
+pub fn create(
+    config: &ExportConfig,
+    columns: &[ColumnInfo],
+    formatter: ColumnFormatter,
+    fetch_size: usize,
+) -> Result<Box<dyn RowWriter>> {
+    Ok(match config.format {
+        ExportFormat::Csv | ExportFormat::Tsv | ExportFormat::Custom => Box::new(TextWriter {
+            writer: DelimitedWriter::from_config(config)?,
+            formatter,
+            include_header: config.include_header,
+        }),
+        ExportFormat::Sql => Box::new(InsertWriter::from_config(config)?),
+        ExportFormat::Xlsx => Box::new(XlsxWriter::new()),
+        ExportFormat::ArrowIpc => Box::new(ArrowWriter::new(config, columns, fetch_size)?),
+        ExportFormat::Avro => Box::new(AvroWriter::new(config, columns)?),
+        ExportFormat::FixedWidth => {
+            let layout = Layout::new(config, columns, &formatter)?;
+            Box::new(FixedWidthWriter::new(config, layout, formatter)?)
+        }
+    })
+}
+
+/// 写入器收到的值对应的输出列：脱敏后可能改变类型的列及转换为文本的二进制列按文本处理（长度为转换后的长度，
+/// 无法确定时为None），脱敏列可为空
+pub fn value_columns(config: &ExportConfig, columns: &[ColumnInfo], masker: &Masker) -> Vec<ColumnInfo> {
+    columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            let mut column = column.clone();
+            if matches!(column.kind, ColumnKind::Binary | ColumnKind::Blob) && lob::encodes_binary(config) {
+                column.kind = ColumnKind::Text;
+                column.size = column.size.and_then(|size| lob::encoded_size(config, size));
+            }
+            if masker.changes_type(i) {
+                column.kind = ColumnKind::Text;
+                column.size = None;
+            }
+            if masker.is_masked(i) {
+                column.nullable = true;
+            }
+            column
+        })
+        .collect()
+}
+
+/// 分隔文本格式：按 `[export.columns]` 规则格式化后写入
+struct TextWriter {
+    writer: DelimitedWriter,
+    formatter: ColumnFormatter,
+    include_header: bool,
+}
+
+impl RowWriter for TextWriter {
+    fn begin(&mut self, out: &mut dyn Write, headers: &[String]) -> Result<()> {
+        if self.include_header {
+            self.writer.write_row(out, headers)?;
+        }
+        Ok(())
+    }
+
+    fn write_row(&mut self, out: &mut dyn Write, values: Vec<Value>) -> Result<()> {
+        self.writer.write_row(out, &self.formatter.format_row(values))
+    }
+
+    fn finish(&mut self, _out: &mut dyn Write) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use crate::db::Value;
+use crate::writer::RowWriter;
+use anyhow::{bail, Context, Result};
+use chrono::Datelike;
+use rust_xlsxwriter::{ColNum, Format, RowNum, Workbook};
+use std::io::{self, Seek, SeekFrom, Write};
+use tracing::warn;
+
+/// Excel工作表的最大行数（含表头）
+const MAX_ROWS: u32 = 1_048_576;
+/// Excel工作表的最大列数
+const MAX_COLUMNS: usize = 16_384;
+/// 单元格文本的最大字符数
+const MAX_CELL_CHARS: usize = 32_767;
+/// Excel数字的有效位数，超出时按文本写入以免丢失精度
+const MAX_NUMBER_DIGITS: usize = 15;
+
+/// XLSX写入器，工作表使用常量内存模式（行数据写入临时文件），超过Excel行数上限时自动新建工作表
+pub struct XlsxWriter {
+    workbook: Workbook,
+    headers: Vec<String>,
+    header_format: Format,
+    date_format: Format,
+    datetime_format: Format,
+    fraction_format: Format,
+    /// 当前工作表序号
+    sheet: usize,
+    /// 当前工作表中下一行的行号
+    row: u32,
+    truncated: u64,
+}
+
+impl XlsxWriter {
+    pub fn new() -> Self {
+        Self {
+            workbook: Workbook::new(),
+            headers: Vec::new(),
+            header_format: Format::new().set_bold(),
+            date_format: Format::new().set_num_format("yyyy-mm-dd"),
+            datetime_format: Format::new().set_num_format("yyyy-mm-dd hh:mm:ss"),
+            fraction_format: Format::new().set_num_format("yyyy-mm-dd hh:mm:ss.000"),
+            sheet: 0,
+            row: 0,
+            truncated: 0,
+        }
+    }
+
+    /// 新建工作表并写入加粗的表头
+    fn add_sheet(&mut self) -> Result<()> {
+        let worksheet = self.workbook.add_worksheet_with_constant_memory();
+        for (i, header) in self.headers.iter().enumerate() {
+            let col = i as ColNum;
+            worksheet.write_string_with_format(0, col, header, &self.header_format)?;
+            worksheet.set_column_width(col, (header.chars().count() + 2).clamp(10, 50) as f64)?;
+        }
+        worksheet.set_freeze_panes(1, 0)?;
+        self.sheet = self.workbook.worksheets().len() - 1;
+        self.row = 1;
+        Ok(())
+    }
+}
+
+impl RowWriter for XlsxWriter {
+    fn begin(&mut self, _out: &mut dyn Write, headers: &[String]) -> Result<()> {
+        if headers.len() > MAX_COLUMNS {
+            bail!("XLSX supports at most {} columns, got {}", MAX_COLUMNS, headers.len());
+        }
+        self.headers = headers.to_vec();
+        self.add_sheet()
+    }
+
+    fn write_row(&mut self, _out: &mut dyn Write, values: Vec<Value>) -> Result<()> {
+        if self.row == MAX_ROWS {
+            self.add_sheet()?;
+        }
+        let row = self.row as RowNum;
+        let worksheet = self.workbook.worksheet_from_index(self.sheet)?;
+        for (i, value) in values.into_iter().enumerate() {
+            let col = i as ColNum;
+            match value {
+                Value::Null => {}
+                Value::Number(text) => match text.parse::<f64>() {
+                    Ok(number) if number.is_finite() && significant_digits(&text) <= MAX_NUMBER_DIGITS => {
+                        worksheet.write_number(row, col, number)?;
+                    }
+                    _ => {
+                        worksheet.write_string(row, col, text)?;
+                    }
+                },
+                // Excel的日期只能表示1900-9999年，范围外的值（如0001-01-01）按文本写入
+                value @ Value::DateTime { .. } if !in_excel_range(&value) => {
+                    worksheet.write_string(row, col, value.into_string())?;
+                }
+                Value::DateTime { value, precision } => {
+                    let format = if precision > 0 {
+                        &self.fraction_format
+                    } else if value.time() == chrono::NaiveTime::MIN {
+                        &self.date_format
+                    } else {
+                        &self.datetime_format
+                    };
+                    worksheet.write_datetime_with_format(row, col, value, format)?;
+                }
+                Value::Boolean(b) => {
+                    worksheet.write_boolean(row, col, b)?;
+                }
+                // Excel没有带时区的日期类型，按文本写入
+                other => {
+                    let mut text = other.into_string();
+                    if text.chars().count() > MAX_CELL_CHARS {
+                        text = text.chars().take(MAX_CELL_CHARS).collect();
+                        self.truncated += 1;
+                    }
+                    worksheet.write_string(row, col, text)?;
+                }
+            }
+        }
+        self.row += 1;
+        Ok(())
+    }
+
+    /// 工作簿先保存到临时文件（ZIP需要可定位的输出），再复制到输出
+    fn finish(&mut self, out: &mut dyn Write) -> Result<()> {
+        if self.truncated > 0 {
+            warn!(
+                "{} values longer than {} characters were truncated (Excel cell limit)",
+                self.truncated, MAX_CELL_CHARS
+            );
+        }
+        let mut file = tempfile::tempfile().context("Failed to create temporary file for XLSX output")?;
+        self.workbook.save_to_writer(&mut file).context("Failed to write XLSX workbook")?;
+        file.seek(SeekFrom::Start(0))?;
+        io::copy(&mut file, out)?;
+        Ok(())
+    }
+}
+
+/// 十进制文本的有效位数（不含符号、小数点、前导零及指数部分）
+fn significant_digits(text: &str) -> usize {
+    let mantissa = text.split(['e', 'E']).next().unwrap_or_default();
+    let digits: String = mantissa.chars().filter(char::is_ascii_digit).collect();
+    let digits = digits.trim_start_matches('0');
+    if mantissa.contains('.') {
+        digits.len()
+    } else {
+        digits.trim_end_matches('0').len()
+    }
+}
+
+/// 日期是否在Excel可表示的范围内（1900-01-01至9999-12-31）
+fn in_excel_range(value: &Value) -> bool {
+    matches!(value, Value::DateTime { value, .. } if (1900..=9999).contains(&value.year()))
+}